@@ -0,0 +1,95 @@
+//!
+//! Units of angles, see [`AngleMode`]
+//!
+//! Trigonometric functions take and give angles in the angle mode of the evaluator, and literals
+//! with a suffix such as `30°` or `1.2rad` are converted to it
+//!
+
+use num_rational::BigRational;
+use num_traits::ToPrimitive;
+
+use crate::decimal;
+use crate::evaluator::NumericMode;
+use crate::expression::BinOp;
+use crate::interval::{self, Interval};
+use crate::units;
+use crate::value::{OperationError, Value};
+
+///
+/// The unit angles are measured in
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AngleMode{
+    #[default]
+    Radians,
+    /// 360 to a turn
+    Degrees,
+    /// 400 to a turn, so that a right angle is 100
+    Gradians
+}
+
+impl AngleMode{
+    ///
+    /// The suffix of a literal in this unit, e.g `30°`
+    ///
+    pub fn suffix(&self) -> &'static str{
+        match self{
+            Self::Radians => "rad",
+            Self::Degrees => "°",
+            Self::Gradians => "grad"
+        }
+    }
+
+    pub fn from_suffix(suffix : &str) -> Option<Self>{
+        [Self::Radians, Self::Degrees, Self::Gradians].into_iter().find(|mode| mode.suffix() == suffix)
+    }
+
+    ///
+    /// A full turn, as a fraction and the power of π it multiplies
+    ///
+    fn turn(&self) -> (BigRational, i32){
+        match self{
+            Self::Radians => (BigRational::from_integer(2.into()), 1),
+            Self::Degrees => (BigRational::from_integer(360.into()), 0),
+            Self::Gradians => (BigRational::from_integer(400.into()), 0)
+        }
+    }
+}
+
+///
+/// `angle`, measured in `from`, measured in `to` instead
+///
+/// Exact angles stay exact between degrees and gradians, conversions from or to radians
+/// involve π, which is a decimal in decimal mode and the interval holding it for intervals
+///
+pub(crate) fn convert(angle : &Value, from : AngleMode, to : AngleMode, mode : NumericMode) -> Result<Value, OperationError>{
+    if from == to{
+        return Ok(angle.clone());
+    }
+    let ((to_turn, to_pi), (from_turn, from_pi)) = (to.turn(), from.turn());
+    let factor = factor(angle, &(to_turn / from_turn), to_pi - from_pi, mode);
+    Value::arithmetic(BinOp::Mul, angle, &factor, mode)
+}
+
+///
+/// `ratio π^power`, `power` being -1, 0 or 1, as a number to multiply `angle` by
+///
+fn factor(angle : &Value, ratio : &BigRational, power : i32, mode : NumericMode) -> Value{
+    if power == 0{
+        return units::constant_for(angle, ratio, mode);
+    }
+    match (angle, mode){
+        (Value::Interval(_), _) | (_, NumericMode::Interval) => {
+            let op = if power > 0 { BinOp::Mul } else { BinOp::Div };
+            Value::Interval(interval::arithmetic(op, Interval::enclosing(ratio), Interval::pi()).expect("π is not zero"))
+        },
+        (Value::Decimal(_), _) | (_, NumericMode::Decimal { .. }) => {
+            Value::Decimal(decimal::times_pi(ratio, power, &mode.decimal_context()))
+        },
+        _ => {
+            let (numer, denom) = (ratio.numer().to_f64().unwrap_or(f64::NAN), ratio.denom().to_f64().unwrap_or(f64::NAN));
+            let pi = std::f64::consts::PI;
+            Value::Number(if power > 0 { numer * pi / denom } else { numer / (denom * pi) })
+        }
+    }
+}
@@ -0,0 +1,803 @@
+use bigdecimal::{BigDecimal, Context, RoundingMode};
+use num_complex::Complex64;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::angle::{self, AngleMode};
+use crate::decimal;
+use crate::evaluator::NumericMode;
+use crate::functions::{number_args, Arity, CallContext, FunctionError, FunctionRegistry};
+use crate::interval::Interval;
+use crate::measurement::Measurement;
+use crate::value::Value;
+
+///
+/// Largest argument of factorial, and second argument of binomial, computed exactly
+///
+const MAX_EXACT_COUNT : u64 = 10_000;
+
+fn add<F, D>(registry : &mut FunctionRegistry, name : &str, arity : Arity, float : F, decimal : D)
+    where F : Fn(&[f64]) -> Result<f64, FunctionError> + Send + Sync + 'static,
+          D : Fn(&[BigDecimal], &Context) -> Result<BigDecimal, FunctionError> + Send + Sync + 'static
+{
+    add_exact(registry, name, arity, float, |_| Ok(None), decimal);
+}
+
+///
+/// Register a function of one argument that never fails
+///
+fn add_unary(registry : &mut FunctionRegistry, name : &str, float : fn(f64) -> f64, decimal : fn(&BigDecimal, &Context) -> BigDecimal){
+    add(registry, name, Arity::Fixed(1), move |args| Ok(float(args[0])), move |args, context| Ok(decimal(&args[0], context)));
+}
+
+///
+/// The arguments as decimals of the precision of `context`, if they are all rationals or decimals
+/// and either one of them is a decimal or the evaluator is in decimal mode
+///
+fn decimal_args(args : &[Value], context : &CallContext) -> Option<Vec<BigDecimal>>{
+    let decimal_mode = matches!(context.numeric_mode, NumericMode::Decimal { .. });
+    if !decimal_mode && !args.iter().any(|a| matches!(a, Value::Decimal(_))){
+        return None;
+    }
+
+    let precision = context.numeric_mode.decimal_context();
+    args.iter()
+        .map(|a| match a{
+            Value::Decimal(d) => Some(d.clone()),
+            Value::Rational(r) => Some(decimal::from_rational(r, &precision)),
+            Value::Integer(i) => Some(decimal::round(BigDecimal::from(i.clone()), &precision)),
+            _ => None
+        })
+        .collect()
+}
+
+///
+/// Register a function that stays exact when all its arguments are rationals or integers
+///
+/// `exact` gives `None` when the result is not a rational, the decimal version is used instead if
+/// the arguments allow it, and the float version otherwise. The result of integer arguments is an
+/// integer when it has no fractional part, and a float otherwise
+///
+fn add_exact<F, E, D>(registry : &mut FunctionRegistry, name : &str, arity : Arity, float : F, exact : E, decimal : D)
+    where F : Fn(&[f64]) -> Result<f64, FunctionError> + Send + Sync + 'static,
+          E : Fn(&[BigRational]) -> Result<Option<BigRational>, FunctionError> + Send + Sync + 'static,
+          D : Fn(&[BigDecimal], &Context) -> Result<BigDecimal, FunctionError> + Send + Sync + 'static
+{
+    let implementation = move |args : &[Value], context : &CallContext| {
+        let rationals = args.iter()
+            .map(|a| match a{
+                Value::Rational(r) => Some(r.clone()),
+                Value::Integer(i) => Some(BigRational::from_integer(i.clone())),
+                _ => None
+            })
+            .collect::<Option<Vec<_>>>();
+
+        if let Some(rationals) = rationals{
+            if let Some(result) = exact(&rationals)?{
+                if !args.iter().all(|a| matches!(a, Value::Integer(_))){
+                    return Ok(Value::Rational(result));
+                }
+                if result.is_integer(){
+                    return Ok(Value::Integer(result.to_integer()));
+                }
+                return Ok(Value::Number(Value::Rational(result).as_number().expect("rationals are numbers")));
+            }
+        }
+        if let Some(decimals) = decimal_args(args, context){
+            return decimal(&decimals, &context.numeric_mode.decimal_context()).map(Value::decimal);
+        }
+        float(&number_args(args)?).map(Value::Number)
+    };
+    registry.register_typed(name, arity, implementation).expect("built-in function names are identifiers");
+}
+
+fn add_exact_unary(registry : &mut FunctionRegistry, name : &str, float : fn(f64) -> f64, exact : fn(&BigRational) -> BigRational,
+    decimal : fn(&BigDecimal) -> BigDecimal)
+{
+    add_exact(registry, name, Arity::Fixed(1), move |args| Ok(float(args[0])), move |args| Ok(Some(exact(&args[0]))),
+        move |args, _| Ok(decimal(&args[0])));
+}
+
+///
+/// The arguments of a function of complex numbers
+///
+fn complex_args(args : &[Value]) -> Result<Vec<Complex64>, FunctionError>{
+    args.iter()
+        .enumerate()
+        .map(|(index, arg)| arg.as_complex().ok_or(FunctionError::ArgumentType{
+            index,
+            expected: "number",
+            found: arg.type_name()
+        }))
+        .collect()
+}
+
+///
+/// Extend the registered function `name` to complex numbers
+///
+/// `complex` is used when an argument is complex, and in complex mode when the
+/// function has no real result for real arguments
+///
+fn extend_complex<C>(registry : &mut FunctionRegistry, name : &str, complex : C)
+    where C : Fn(&[Complex64]) -> Result<Value, FunctionError> + Send + Sync + 'static
+{
+    let real = registry.remove(name).expect("complex functions extend a registered real function");
+    let implementation = move |args : &[Value], context : &CallContext| {
+        if !args.iter().any(|a| matches!(a, Value::Complex(_))){
+            let result = (real.implementation)(args, context);
+            let undefined = matches!(result, Err(FunctionError::Domain(_))) || matches!(result, Ok(Value::Number(n)) if n.is_nan());
+            if !undefined || context.numeric_mode != NumericMode::Complex{
+                return result;
+            }
+        }
+        complex(&complex_args(args)?)
+    };
+    registry.register_typed(name, real.arity, implementation).expect("built-in function names are identifiers");
+}
+
+fn extend_complex_unary(registry : &mut FunctionRegistry, name : &str, complex : fn(Complex64) -> Complex64){
+    extend_complex(registry, name, move |args| Ok(Value::complex(complex(args[0]))));
+}
+
+///
+/// Register a function taking numbers of any kind, including complex ones
+///
+fn add_complex<C>(registry : &mut FunctionRegistry, name : &str, arity : Arity, f : C)
+    where C : Fn(&[Value], &[Complex64]) -> Result<Value, FunctionError> + Send + Sync + 'static
+{
+    registry.register_typed(name, arity, move |args, _| f(args, &complex_args(args)?)).expect("built-in function names are identifiers");
+}
+
+///
+/// The arguments of a function of intervals, other real numbers are enclosed in intervals
+///
+fn interval_args(args : &[Value]) -> Result<Vec<Interval>, FunctionError>{
+    args.iter()
+        .enumerate()
+        .map(|(index, arg)| arg.as_interval().ok_or(FunctionError::ArgumentType{
+            index,
+            expected: "number",
+            found: arg.type_name()
+        }))
+        .collect()
+}
+
+///
+/// Extend the registered function `name` to intervals, `interval` is used when an argument is an interval
+///
+fn extend_interval<I>(registry : &mut FunctionRegistry, name : &str, interval : I)
+    where I : Fn(&[Interval]) -> Result<Interval, FunctionError> + Send + Sync + 'static
+{
+    let real = registry.remove(name).expect("interval functions extend a registered real function");
+    let implementation = move |args : &[Value], context : &CallContext| {
+        if !args.iter().any(|a| matches!(a, Value::Interval(_))){
+            return (real.implementation)(args, context);
+        }
+        interval(&interval_args(args)?).map(Value::Interval)
+    };
+    registry.register_typed(name, real.arity, implementation).expect("built-in function names are identifiers");
+}
+
+fn extend_interval_unary(registry : &mut FunctionRegistry, name : &str, interval : fn(&Interval) -> Interval){
+    extend_interval(registry, name, move |args| Ok(interval(&args[0])));
+}
+
+///
+/// Extend `name` to intervals with a function defined on part of the real numbers, `outside` tells why it is not
+///
+fn extend_interval_partial(registry : &mut FunctionRegistry, name : &str, interval : fn(&Interval) -> Option<Interval>, outside : &'static str){
+    extend_interval(registry, name, move |args| interval(&args[0]).ok_or_else(|| domain(outside)));
+}
+
+///
+/// Register a function of an interval, other real numbers are taken as intervals holding only themselves
+///
+fn add_interval(registry : &mut FunctionRegistry, name : &str, f : fn(&Interval) -> f64){
+    registry.register_typed(name, Arity::Fixed(1), move |args, _| Ok(Value::Number(f(&interval_args(args)?[0]))))
+        .expect("built-in function names are identifiers");
+}
+
+///
+/// Extend the registered function `name` of exact numbers to intervals holding a single number, e.g factorial
+///
+/// The function is computed exactly, as in rational mode, and its result enclosed in an interval
+///
+fn extend_interval_exact(registry : &mut FunctionRegistry, name : &'static str){
+    let real = registry.remove(name).expect("interval functions extend a registered real function");
+    let implementation = move |args : &[Value], context : &CallContext| {
+        if !args.iter().any(|a| matches!(a, Value::Interval(_))){
+            return (real.implementation)(args, context);
+        }
+        let points = interval_args(args)?.iter()
+            .map(|i| (i.lo() == i.hi()).then(|| BigRational::from_float(i.lo())).flatten().map(Value::Rational))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| domain(&format!("{name} of an interval that is not a single number")))?;
+        let exact = CallContext{ numeric_mode: NumericMode::Rational, ..context.clone() };
+        match (real.implementation)(&points, &exact)?{
+            result @ (Value::Integer(_) | Value::Rational(_)) => Ok(Value::Interval(result.as_interval().expect("exact numbers have an enclosing interval"))),
+            _ => Err(domain(&format!("{name} arguments are too large for an interval")))
+        }
+    };
+    registry.register_typed(name, real.arity, implementation).expect("built-in function names are identifiers");
+}
+
+///
+/// Shared implementation of the logarithms of intervals, which must only hold positive numbers
+///
+fn interval_log(x : &Interval, log : impl FnOnce(&Interval) -> Option<Interval>) -> Result<Interval, FunctionError>{
+    if x.lo() == 0.0 && x.hi() == 0.0{
+        return Err(FunctionError::LogZero);
+    }
+    log(x).ok_or_else(|| domain("logarithm of an interval holding zero or negative numbers"))
+}
+
+///
+/// The arguments of a function of measurements, other real numbers have no uncertainty
+///
+fn measurement_args(args : &[Value]) -> Result<Vec<Measurement>, FunctionError>{
+    args.iter()
+        .enumerate()
+        .map(|(index, arg)| arg.as_measurement().ok_or(FunctionError::ArgumentType{
+            index,
+            expected: "number",
+            found: arg.type_name()
+        }))
+        .collect()
+}
+
+///
+/// Extend the registered function `name` to measurements, used when an argument is a measurement
+///
+/// The value is that of the function on the values of the arguments, so it fails where the function does,
+/// and `partials` gives the derivatives with respect to each argument from their values and the result
+///
+fn extend_measurement<P>(registry : &mut FunctionRegistry, name : &'static str, partials : P)
+    where P : Fn(&[f64], f64) -> Vec<f64> + Send + Sync + 'static
+{
+    let real = registry.remove(name).expect("measurement functions extend a registered real function");
+    let implementation = move |args : &[Value], context : &CallContext| {
+        if !args.iter().any(|a| matches!(a, Value::Measurement(_))){
+            return (real.implementation)(args, context);
+        }
+        let args = measurement_args(args)?;
+        let values = args.iter().map(|a| Value::Number(a.value())).collect::<Vec<_>>();
+        let float = CallContext{ numeric_mode: NumericMode::Float, ..context.clone() };
+        let value = (real.implementation)(&values, &float)?
+            .as_number()
+            .ok_or_else(|| domain(&format!("{name} of an uncertain number must be a real number")))?;
+        let values = args.iter().map(Measurement::value).collect::<Vec<_>>();
+        Ok(Value::Measurement(Measurement::propagate(value, &args, &partials(&values, value))))
+    };
+    registry.register_typed(name, real.arity, implementation).expect("built-in function names are identifiers");
+}
+
+fn extend_measurement_unary(registry : &mut FunctionRegistry, name : &'static str, derivative : fn(f64, f64) -> f64){
+    extend_measurement(registry, name, move |x, result| vec![derivative(x[0], result)]);
+}
+
+///
+/// Derivatives of a function that gives one of its arguments, e.g min: only the first argument it gives counts
+///
+fn selected(args : &[f64], result : f64) -> Vec<f64>{
+    let index = args.iter().position(|x| *x == result);
+    (0..args.len()).map(|i| if Some(i) == index { 1.0 } else { 0.0 }).collect()
+}
+
+///
+/// The conversion of the argument at `index` to another unit of angle, which only fails for values that are not numbers
+///
+fn convert_angle(arg : &Value, index : usize, from : AngleMode, to : AngleMode, context : &CallContext) -> Result<Value, FunctionError>{
+    angle::convert(arg, from, to, context.numeric_mode).map_err(|_| FunctionError::ArgumentType{
+        index,
+        expected: "number",
+        found: arg.type_name()
+    })
+}
+
+///
+/// Make `name`, registered as a function of an angle in radians at `index`, take its angle in the angle mode of the call
+///
+fn angle_argument(registry : &mut FunctionRegistry, name : &str, index : usize){
+    let radians = registry.remove(name).expect("functions of angles are registered");
+    let implementation = move |args : &[Value], context : &CallContext| {
+        let mut args = args.to_vec();
+        args[index] = convert_angle(&args[index], index, context.angle_mode, AngleMode::Radians, context)?;
+        (radians.implementation)(&args, context)
+    };
+    registry.register_typed(name, radians.arity, implementation).expect("built-in function names are identifiers");
+}
+
+///
+/// Make `name`, registered as a function giving an angle in radians, give it in the angle mode of the call
+///
+fn angle_result(registry : &mut FunctionRegistry, name : &str){
+    let radians = registry.remove(name).expect("functions giving angles are registered");
+    let implementation = move |args : &[Value], context : &CallContext| {
+        let angle = (radians.implementation)(args, context)?;
+        convert_angle(&angle, 0, AngleMode::Radians, context.angle_mode, context)
+    };
+    registry.register_typed(name, radians.arity, implementation).expect("built-in function names are identifiers");
+}
+
+///
+/// The square root of `r` if it is a rational, `r` must not be negative
+///
+fn exact_sqrt(r : &BigRational) -> Option<BigRational>{
+    let (numer, denom) = (r.numer().sqrt(), r.denom().sqrt());
+    (&numer * &numer == *r.numer() && &denom * &denom == *r.denom()).then(|| BigRational::new(numer, denom))
+}
+
+///
+/// `r` as the argument of factorial or binomial, which only take non-negative integers
+///
+fn count(r : &BigRational, what : &str) -> Result<BigInt, FunctionError>{
+    if !r.is_integer() || r.is_negative(){
+        return Err(domain(&format!("{what} of a negative or fractional number")));
+    }
+    Ok(r.to_integer())
+}
+
+///
+/// [`count`] of a decimal, those too large to be written as a fraction are too large to count
+///
+fn decimal_count(x : &BigDecimal, what : &str) -> Result<BigInt, FunctionError>{
+    let r = decimal::to_rational(x).ok_or_else(|| domain(&format!("{what} argument is too large for a decimal")))?;
+    count(&r, what)
+}
+
+fn float_count(x : f64, what : &str) -> Result<f64, FunctionError>{
+    if x < 0.0 || x.fract() != 0.0{
+        return Err(domain(&format!("{what} of a negative or fractional number")));
+    }
+    Ok(x)
+}
+
+///
+/// `n!`, or `None` if `n` is too large to compute exactly
+///
+fn factorial(n : &BigInt) -> Option<BigInt>{
+    let n = n.to_u64().filter(|&n| n <= MAX_EXACT_COUNT)?;
+    Some((2..=n).map(BigInt::from).product())
+}
+
+///
+/// `n` choose `k`, or `None` if the smaller of `k` and `n - k` is too large to compute exactly
+///
+fn binomial(n : &BigInt, k : &BigInt) -> Option<BigInt>{
+    if k.is_negative() || k > n{
+        return Some(BigInt::zero());
+    }
+    let k = k.min(&(n - k)).to_u64().filter(|&k| k <= MAX_EXACT_COUNT)?;
+    // Each partial product is itself a binomial coefficient, so the divisions are exact
+    Some((0..k).fold(BigInt::one(), |acc, i| acc * (n - i) / (i + 1)))
+}
+
+fn domain(message : &str) -> FunctionError{
+    FunctionError::Domain(message.to_string())
+}
+
+///
+/// The result of a decimal function, or a domain error if it does not fit in a decimal
+///
+fn in_range(result : Option<BigDecimal>) -> Result<BigDecimal, FunctionError>{
+    result.ok_or_else(|| domain("result is out of the range of decimals"))
+}
+
+///
+/// The result of a decimal function of an angle, or a domain error if the angle is too large to reduce to a turn
+///
+fn reduced(result : Option<BigDecimal>) -> Result<BigDecimal, FunctionError>{
+    result.ok_or_else(|| domain("angle has too many integer digits to reduce it to a turn"))
+}
+
+///
+/// Shared implementation of the logarithms, `value` must be positive
+///
+fn checked_log<T : Signed>(value : T, log : impl FnOnce(T) -> T) -> Result<T, FunctionError>{
+    if value.is_zero(){
+        Err(FunctionError::LogZero)
+    }
+    else if value.is_negative(){
+        Err(domain("logarithm of a negative number"))
+    }
+    else{
+        Ok(log(value))
+    }
+}
+
+///
+/// Add the built-in functions to `registry`
+///
+pub fn register(registry : &mut FunctionRegistry){
+    // Trigonometry
+    add(registry, "sin", Arity::Fixed(1), |args| Ok(args[0].sin()), |args, context| reduced(decimal::sin(&args[0], context)));
+    add(registry, "cos", Arity::Fixed(1), |args| Ok(args[0].cos()), |args, context| reduced(decimal::cos(&args[0], context)));
+    add(registry, "tan", Arity::Fixed(1), |args| Ok(args[0].tan()), |args, context| reduced(decimal::tan(&args[0], context)));
+    add_unary(registry, "atan", f64::atan, decimal::atan);
+    add(registry, "asin", Arity::Fixed(1), |args| {
+        if args[0].abs() > 1.0{
+            return Err(domain("asin is only defined between -1 and 1"));
+        }
+        Ok(args[0].asin())
+    }, |args, context| {
+        if args[0].abs() > BigDecimal::one(){
+            return Err(domain("asin is only defined between -1 and 1"));
+        }
+        Ok(decimal::asin(&args[0], context))
+    });
+    add(registry, "acos", Arity::Fixed(1), |args| {
+        if args[0].abs() > 1.0{
+            return Err(domain("acos is only defined between -1 and 1"));
+        }
+        Ok(args[0].acos())
+    }, |args, context| {
+        if args[0].abs() > BigDecimal::one(){
+            return Err(domain("acos is only defined between -1 and 1"));
+        }
+        Ok(decimal::acos(&args[0], context))
+    });
+    // atan2(y, x)
+    add(registry, "atan2", Arity::Fixed(2), |args| Ok(args[0].atan2(args[1])),
+        |args, context| Ok(decimal::atan2(&args[0], &args[1], context)));
+
+    // Hyperbolic functions
+    add(registry, "sinh", Arity::Fixed(1), |args| Ok(args[0].sinh()), |args, context| in_range(decimal::sinh(&args[0], context)));
+    add(registry, "cosh", Arity::Fixed(1), |args| Ok(args[0].cosh()), |args, context| in_range(decimal::cosh(&args[0], context)));
+    add_unary(registry, "tanh", f64::tanh, decimal::tanh);
+    add_unary(registry, "asinh", f64::asinh, decimal::asinh);
+    add(registry, "acosh", Arity::Fixed(1), |args| {
+        if args[0] < 1.0{
+            return Err(domain("acosh is only defined from 1"));
+        }
+        Ok(args[0].acosh())
+    }, |args, context| {
+        if args[0] < BigDecimal::one(){
+            return Err(domain("acosh is only defined from 1"));
+        }
+        Ok(decimal::acosh(&args[0], context))
+    });
+    add(registry, "atanh", Arity::Fixed(1), |args| {
+        if args[0].abs() >= 1.0{
+            return Err(domain("atanh is only defined strictly between -1 and 1"));
+        }
+        Ok(args[0].atanh())
+    }, |args, context| {
+        if args[0].abs() >= BigDecimal::one(){
+            return Err(domain("atanh is only defined strictly between -1 and 1"));
+        }
+        Ok(decimal::atanh(&args[0], context))
+    });
+
+    // Powers and roots
+    add_exact(registry, "sqrt", Arity::Fixed(1), |args| {
+        if args[0] < 0.0{
+            return Err(domain("square root of a negative number"));
+        }
+        Ok(args[0].sqrt())
+    }, |args| {
+        if args[0].is_negative(){
+            return Err(domain("square root of a negative number"));
+        }
+        Ok(exact_sqrt(&args[0]))
+    }, |args, context| {
+        if args[0].is_negative(){
+            return Err(domain("square root of a negative number"));
+        }
+        Ok(decimal::sqrt(&args[0], context))
+    });
+    add_unary(registry, "cbrt", f64::cbrt, decimal::cbrt);
+    add(registry, "exp", Arity::Fixed(1), |args| Ok(args[0].exp()), |args, context| in_range(decimal::exp(&args[0], context)));
+    // Nth root, arg 1 is the root number and arg 2 is the target
+    add(registry, "nrt", Arity::Fixed(2), |args| {
+        let (n, target) = (args[0], args[1]);
+        if n == 0.0{
+            return Err(domain("zeroth root"));
+        }
+        if target < 0.0{
+            // Odd roots of negative numbers are real
+            if n.fract() == 0.0 && n % 2.0 != 0.0{
+                return Ok(-(-target).powf(1.0 / n));
+            }
+            return Err(domain("even root of a negative number"));
+        }
+        Ok(target.powf(1.0 / n))
+    }, |args, context| {
+        let [n, target] = args else { unreachable!("nrt takes 2 arguments") };
+        if n.is_zero(){
+            return Err(domain("zeroth root"));
+        }
+        if target.is_negative() && !decimal::is_odd_integer(n){
+            return Err(domain("even root of a negative number"));
+        }
+        in_range(decimal::nrt(n, target, context))
+    });
+    add(registry, "hypot", Arity::Fixed(2), |args| Ok(args[0].hypot(args[1])),
+        |args, context| Ok(decimal::hypot(&args[0], &args[1], context)));
+
+    // Logarithms
+    add(registry, "ln", Arity::Fixed(1), |args| checked_log(args[0], f64::ln),
+        |args, context| checked_log(args[0].clone(), |v| decimal::ln(&v, context)));
+    add(registry, "log10", Arity::Fixed(1), |args| checked_log(args[0], f64::log10),
+        |args, context| checked_log(args[0].clone(), |v| decimal::log(&BigDecimal::from(10), &v, context)));
+    add(registry, "log2", Arity::Fixed(1), |args| checked_log(args[0], f64::log2),
+        |args, context| checked_log(args[0].clone(), |v| decimal::log(&BigDecimal::from(2), &v, context)));
+    // log(base, value)
+    add(registry, "log", Arity::Fixed(2), |args| {
+        let (base, value) = (args[0], args[1]);
+        if base <= 0.0 || base == 1.0{
+            return Err(domain("logarithm base must be positive and not 1"));
+        }
+        checked_log(value, |v| v.log(base))
+    }, |args, context| {
+        let [base, value] = args else { unreachable!("log takes 2 arguments") };
+        if !base.is_positive() || base.is_one(){
+            return Err(domain("logarithm base must be positive and not 1"));
+        }
+        checked_log(value.clone(), |v| decimal::log(base, &v, context))
+    });
+
+    // Rounding and sign
+    add_exact_unary(registry, "abs", f64::abs, BigRational::abs, BigDecimal::abs);
+    // Unlike f64::signum, zero has no sign
+    add_exact_unary(registry, "sign", |x| if x == 0.0 { 0.0 } else { x.signum() }, BigRational::signum, BigDecimal::signum);
+    add_exact_unary(registry, "floor", f64::floor, BigRational::floor, |x| decimal::to_integer(x, RoundingMode::Floor));
+    add_exact_unary(registry, "ceil", f64::ceil, BigRational::ceil, |x| decimal::to_integer(x, RoundingMode::Ceiling));
+    // Halves are rounded away from zero, like f64::round
+    add_exact_unary(registry, "round", f64::round, BigRational::round, |x| decimal::to_integer(x, RoundingMode::HalfUp));
+    add_exact_unary(registry, "trunc", f64::trunc, BigRational::trunc, |x| decimal::to_integer(x, RoundingMode::Down));
+
+    // Comparison
+    add_exact(registry, "min", Arity::Variadic { min: 1 },
+        |args| Ok(args.iter().copied().fold(f64::INFINITY, f64::min)),
+        |args| Ok(args.iter().min().cloned()),
+        |args, _| Ok(args.iter().min().cloned().expect("min takes at least 1 argument")));
+    add_exact(registry, "max", Arity::Variadic { min: 1 },
+        |args| Ok(args.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        |args| Ok(args.iter().max().cloned()),
+        |args, _| Ok(args.iter().max().cloned().expect("max takes at least 1 argument")));
+    // clamp(value, low, high)
+    add_exact(registry, "clamp", Arity::Fixed(3), |args| {
+        let (value, low, high) = (args[0], args[1], args[2]);
+        if low > high{
+            return Err(domain("clamp lower bound is above the upper bound"));
+        }
+        Ok(value.clamp(low, high))
+    }, |args| {
+        let [value, low, high] = args else { unreachable!("clamp takes 3 arguments") };
+        if low > high{
+            return Err(domain("clamp lower bound is above the upper bound"));
+        }
+        Ok(Some(value.clone().clamp(low.clone(), high.clone())))
+    }, |args, _| {
+        let [value, low, high] = args else { unreachable!("clamp takes 3 arguments") };
+        if low > high{
+            return Err(domain("clamp lower bound is above the upper bound"));
+        }
+        Ok(value.clone().clamp(low.clone(), high.clone()))
+    });
+
+    // Combinatorics, exact for integers, out of range results are infinite floats
+    add_exact(registry, "factorial", Arity::Fixed(1), |args| {
+        let n = float_count(args[0], "factorial")?;
+        // Larger factorials overflow, and would take as many steps to do so
+        if n > 170.0{
+            return Ok(f64::INFINITY);
+        }
+        Ok((2..=n as u64).map(|i| i as f64).product())
+    }, |args| {
+        Ok(factorial(&count(&args[0], "factorial")?).map(BigRational::from_integer))
+    }, |args, context| {
+        let n = decimal_count(&args[0], "factorial")?;
+        let result = factorial(&n).ok_or_else(|| domain("factorial argument is too large for a decimal"))?;
+        Ok(decimal::round(BigDecimal::from(result), context))
+    });
+    // binomial(n, k), the number of ways to choose k items among n
+    add_exact(registry, "binomial", Arity::Fixed(2), |args| {
+        let n = float_count(args[0], "binomial coefficient")?;
+        let k = args[1];
+        if k.fract() != 0.0{
+            return Err(domain("binomial coefficient of a negative or fractional number"));
+        }
+        if k < 0.0 || k > n{
+            return Ok(0.0);
+        }
+        let k = k.min(n - k);
+        let (mut result, mut i) = (1.0_f64, 0.0);
+        while i < k && result.is_finite(){
+            result = result * (n - i) / (i + 1.0);
+            i += 1.0;
+        }
+        Ok(result.round())
+    }, |args| {
+        let n = count(&args[0], "binomial coefficient")?;
+        if !args[1].is_integer(){
+            return Err(domain("binomial coefficient of a negative or fractional number"));
+        }
+        Ok(binomial(&n, &args[1].to_integer()).map(BigRational::from_integer))
+    }, |args, context| {
+        let n = decimal_count(&args[0], "binomial coefficient")?;
+        let k = decimal::to_rational(&args[1]).ok_or_else(|| domain("binomial coefficient arguments are too large for a decimal"))?;
+        if !k.is_integer(){
+            return Err(domain("binomial coefficient of a negative or fractional number"));
+        }
+        let result = binomial(&n, &k.to_integer()).ok_or_else(|| domain("binomial coefficient arguments are too large for a decimal"))?;
+        Ok(decimal::round(BigDecimal::from(result), context))
+    });
+
+    // Complex extensions of the functions above
+    for (name, f) in [
+        ("sin", Complex64::sin as fn(Complex64) -> Complex64), ("cos", Complex64::cos), ("tan", Complex64::tan),
+        ("asin", Complex64::asin), ("acos", Complex64::acos), ("atan", Complex64::atan),
+        ("sinh", Complex64::sinh), ("cosh", Complex64::cosh), ("tanh", Complex64::tanh),
+        ("asinh", Complex64::asinh), ("acosh", Complex64::acosh), ("atanh", Complex64::atanh),
+        ("sqrt", Complex64::sqrt), ("cbrt", Complex64::cbrt), ("exp", Complex64::exp), ("ln", Complex64::ln)
+    ]{
+        extend_complex_unary(registry, name, f);
+    }
+    extend_complex_unary(registry, "log10", |z| z.ln() / std::f64::consts::LN_10);
+    extend_complex_unary(registry, "log2", |z| z.ln() / std::f64::consts::LN_2);
+    extend_complex(registry, "log", |args| {
+        let (base, value) = (args[0], args[1]);
+        if base == Complex64::from(0.0) || base == Complex64::from(1.0){
+            return Err(domain("logarithm base must not be 0 or 1"));
+        }
+        if value == Complex64::from(0.0){
+            return Err(FunctionError::LogZero);
+        }
+        Ok(Value::complex(value.ln() / base.ln()))
+    });
+    extend_complex(registry, "nrt", |args| {
+        let (n, target) = (args[0], args[1]);
+        if n == Complex64::from(0.0){
+            return Err(domain("zeroth root"));
+        }
+        // The square root is exact where the power is not, e.g for sqrt(-4) = 2i
+        if n == Complex64::from(2.0){
+            return Ok(Value::complex(target.sqrt()));
+        }
+        Ok(Value::complex(target.powc(n.inv())))
+    });
+    extend_complex(registry, "abs", |args| Ok(Value::Number(args[0].norm())));
+
+    // Interval extensions of the functions above, with bounds rounded outward
+    for (name, f) in [
+        ("sin", Interval::sin as fn(&Interval) -> Interval), ("cos", Interval::cos), ("atan", Interval::atan),
+        ("sinh", Interval::sinh), ("cosh", Interval::cosh), ("tanh", Interval::tanh), ("asinh", Interval::asinh),
+        ("cbrt", Interval::cbrt), ("exp", Interval::exp), ("abs", Interval::abs)
+    ]{
+        extend_interval_unary(registry, name, f);
+    }
+    for (name, f) in [
+        ("floor", f64::floor as fn(f64) -> f64), ("ceil", f64::ceil), ("round", f64::round), ("trunc", f64::trunc),
+        ("sign", |x| if x == 0.0 { 0.0 } else { x.signum() })
+    ]{
+        extend_interval(registry, name, move |args| Ok(args[0].exact(f)));
+    }
+    extend_interval_partial(registry, "tan", Interval::tan, "tan is undefined at the odd multiples of π/2 the interval may hold");
+    extend_interval_partial(registry, "asin", Interval::asin, "asin is only defined between -1 and 1");
+    extend_interval_partial(registry, "acos", Interval::acos, "acos is only defined between -1 and 1");
+    extend_interval_partial(registry, "acosh", Interval::acosh, "acosh is only defined from 1");
+    extend_interval_partial(registry, "atanh", Interval::atanh, "atanh is only defined strictly between -1 and 1");
+    extend_interval_partial(registry, "sqrt", Interval::sqrt, "square root of an interval holding negative numbers");
+    extend_interval(registry, "ln", |args| interval_log(&args[0], Interval::ln));
+    extend_interval(registry, "log10", |args| interval_log(&args[0], Interval::log10));
+    extend_interval(registry, "log2", |args| interval_log(&args[0], Interval::log2));
+    extend_interval(registry, "min", |args| Ok(Interval::min(args)));
+    extend_interval(registry, "max", |args| Ok(Interval::max(args)));
+    extend_interval(registry, "clamp", |args| {
+        let [value, low, high] = args else { unreachable!("clamp takes 3 arguments") };
+        if low.lo() > high.hi(){
+            return Err(domain("clamp lower bound is above the upper bound"));
+        }
+        Ok(Interval::max(&[*low, Interval::min(&[*value, *high])]))
+    });
+    extend_interval(registry, "log", |args| {
+        let (base, value) = (&args[0], &args[1]);
+        if base.lo() <= 0.0 || base.contains(1.0){
+            return Err(domain("logarithm base must be positive and not 1, an interval base must not hold 1"));
+        }
+        interval_log(value, |v| v.log(base))
+    });
+    extend_interval(registry, "nrt", |args| {
+        let (n, target) = (&args[0], &args[1]);
+        if n.contains(0.0){
+            return Err(domain("zeroth root, the root number interval holds zero"));
+        }
+        target.nrt(n).ok_or_else(|| domain("root of an interval holding negative numbers, which is only real for a single odd root number"))
+    });
+    extend_interval(registry, "hypot", |args| Ok(args[0].hypot(&args[1])));
+    extend_interval(registry, "atan2", |args| Ok(args[0].atan2(&args[1])));
+    extend_interval_exact(registry, "factorial");
+    extend_interval_exact(registry, "binomial");
+
+    // Measurement extensions, with the derivatives that propagate the uncertainties to first order
+    for (name, derivative) in [
+        ("sin", (|x, _| x.cos()) as fn(f64, f64) -> f64), ("cos", |x, _| -x.sin()), ("tan", |x, _| 1.0 / x.cos().powi(2)),
+        ("asin", |x, _| 1.0 / (1.0 - x * x).sqrt()), ("acos", |x, _| -1.0 / (1.0 - x * x).sqrt()),
+        ("atan", |x, _| 1.0 / (1.0 + x * x)),
+        ("sinh", |x, _| x.cosh()), ("cosh", |x, _| x.sinh()), ("tanh", |_, y| 1.0 - y * y),
+        ("asinh", |x, _| 1.0 / (x * x + 1.0).sqrt()), ("acosh", |x, _| 1.0 / (x * x - 1.0).sqrt()),
+        ("atanh", |x, _| 1.0 / (1.0 - x * x)),
+        ("sqrt", |_, y| 0.5 / y), ("cbrt", |_, y| 1.0 / (3.0 * y * y)), ("exp", |_, y| y),
+        ("ln", |x, _| 1.0 / x), ("log10", |x, _| 1.0 / (x * std::f64::consts::LN_10)),
+        ("log2", |x, _| 1.0 / (x * std::f64::consts::LN_2)),
+        ("abs", |x, _| if x == 0.0 { 0.0 } else { x.signum() }),
+        // Steps, constant between their jumps
+        ("floor", |_, _| 0.0), ("ceil", |_, _| 0.0), ("round", |_, _| 0.0), ("trunc", |_, _| 0.0), ("sign", |_, _| 0.0)
+    ]{
+        extend_measurement_unary(registry, name, derivative);
+    }
+    extend_measurement(registry, "atan2", |args, _| {
+        let (y, x) = (args[0], args[1]);
+        let r2 = x * x + y * y;
+        vec![x / r2, -y / r2]
+    });
+    extend_measurement(registry, "hypot", |args, h| vec![args[0] / h, args[1] / h]);
+    extend_measurement(registry, "nrt", |args, root| {
+        let (n, target) = (args[0], args[1]);
+        vec![-root * target.abs().ln() / (n * n), root / (n * target)]
+    });
+    extend_measurement(registry, "log", |args, _| {
+        let (base, value) = (args[0], args[1]);
+        vec![-value.ln() / (base * base.ln().powi(2)), 1.0 / (value * base.ln())]
+    });
+    for name in ["min", "max", "clamp"]{
+        extend_measurement(registry, name, selected);
+    }
+
+    // Bounds of intervals, a real number is its own bounds
+    add_interval(registry, "lo", Interval::lo);
+    add_interval(registry, "hi", Interval::hi);
+    add_interval(registry, "mid", Interval::mid);
+    add_interval(registry, "width", Interval::width);
+
+    // Parts of complex numbers, real numbers are their own real part
+    add_complex(registry, "re", Arity::Fixed(1), |values, args| match values[0]{
+        Value::Complex(_) => Ok(Value::Number(args[0].re)),
+        _ => Ok(values[0].clone())
+    });
+    add_complex(registry, "im", Arity::Fixed(1), |_, args| Ok(Value::Number(args[0].im)));
+    add_complex(registry, "conj", Arity::Fixed(1), |values, args| match values[0]{
+        Value::Complex(_) => Ok(Value::complex(args[0].conj())),
+        _ => Ok(values[0].clone())
+    });
+    // Angle from the positive real axis, between -π and π
+    add_complex(registry, "arg", Arity::Fixed(1), |_, args| Ok(Value::Number(args[0].arg())));
+    // polar(r, theta) and rect(re, im) build a complex number, abs and arg give back the polar form
+    add_complex(registry, "polar", Arity::Fixed(2), |values, _| {
+        let [r, theta] = number_args(values)?[..] else { unreachable!("polar takes 2 arguments") };
+        Ok(Value::complex(Complex64::from_polar(r, theta)))
+    });
+    add_complex(registry, "rect", Arity::Fixed(2), |values, _| {
+        let [re, im] = number_args(values)?[..] else { unreachable!("rect takes 2 arguments") };
+        Ok(Value::complex(Complex64::new(re, im)))
+    });
+    // Intervals are real numbers, their own real part
+    extend_interval(registry, "re", |args| Ok(args[0]));
+    extend_interval(registry, "im", |_| Ok(Interval::point(0.0)));
+    extend_interval(registry, "conj", |args| Ok(args[0]));
+    extend_interval(registry, "arg", |args| {
+        let x = args[0];
+        if x.lo() >= 0.0{
+            return Ok(Interval::point(0.0));
+        }
+        Ok(if x.hi() < 0.0 { Interval::pi() } else { Interval::new(0.0, Interval::pi().hi()).expect("π is positive") })
+    });
+    for name in ["polar", "rect"]{
+        extend_interval(registry, name, move |_| Err(domain(&format!("{name} builds a complex number, which intervals do not support"))));
+    }
+
+    // Angles, the functions above take and give radians whatever the angle mode
+    for (name, index) in [("sin", 0), ("cos", 0), ("tan", 0), ("polar", 1)]{
+        angle_argument(registry, name, index);
+    }
+    for name in ["asin", "acos", "atan", "atan2", "arg"]{
+        angle_result(registry, name);
+    }
+    // deg(x), rad(x) and grad(x) are the angle x of that unit in the angle mode, like the literals `30°` or `1.2rad`
+    for (name, unit) in [("deg", AngleMode::Degrees), ("rad", AngleMode::Radians), ("grad", AngleMode::Gradians)]{
+        registry.register_typed(name, Arity::Fixed(1), move |args, context| convert_angle(&args[0], 0, unit, context.angle_mode, context))
+            .expect("built-in function names are identifiers");
+    }
+}
@@ -0,0 +1,118 @@
+use crate::error::CalcError;
+use crate::evaluator::{Evaluator, ProgramError, StatementOutcome, Value};
+use crate::expression::{ParseOptions, ParserError as ArithmeticParserError};
+use crate::lexer::{Lexer, Position, Span};
+use crate::program::Program;
+use crate::statement::{ParserError, Statement};
+
+///
+/// High level entry point into the calculator
+///
+/// Owns an [`Evaluator`], so variables assigned by one call to [`Calculator::eval`]
+/// remain visible to the following ones
+///
+pub struct Calculator{
+    evaluator : Evaluator,
+    parse_options : ParseOptions
+}
+
+impl Default for Calculator{
+    fn default() -> Self{
+        Self::new()
+    }
+}
+
+impl Calculator{
+    pub fn new() -> Self{
+        Self{
+            evaluator: Evaluator::new(),
+            parse_options: ParseOptions::default()
+        }
+    }
+
+    ///
+    /// Lex, parse and evaluate the statements of `input`
+    ///
+    /// Returns the value of the last statement that has one, for an assignment that is the assigned value.
+    /// Definitions have no value, so an input made only of them is not run, see [`Calculator::execute`]
+    ///
+    pub fn eval(&mut self, input : &str) -> Result<Value, CalcError>{
+        let program = self.parse(input)?;
+        let last = Self::last_statement(&program, input)?;
+        if !program.statements.iter().any(|s| matches!(s, Statement::Expr(_) | Statement::Assignment { .. })){
+            return Err(CalcError::Parse(ParserError::Definition(last.span())));
+        }
+
+        let mut values = self.execute_program(&program)?;
+        Ok(values.pop().expect("a statement has a value"))
+    }
+
+    ///
+    /// Lex, parse and evaluate the statements of `input`, returning what the last one did
+    ///
+    /// Unlike [`Calculator::eval`] this accepts input ending with a definition
+    ///
+    pub fn execute(&mut self, input : &str) -> Result<StatementOutcome, CalcError>{
+        let program = self.parse(input)?;
+        Self::last_statement(&program, input)?;
+
+        let mut outcomes = self.evaluator.run_program(&program).map_err(|e| Self::failure(&program, e))?;
+        Ok(outcomes.pop().expect("the program has a statement"))
+    }
+
+    ///
+    /// Lex, parse and evaluate the statements of `input`, returning the value of each that has one
+    ///
+    pub fn run(&mut self, input : &str) -> Result<Vec<Value>, CalcError>{
+        let program = self.parse(input)?;
+        self.execute_program(&program)
+    }
+
+    fn parse(&self, input : &str) -> Result<Program, CalcError>{
+        let toks = Lexer::new(input.to_string()).all()?;
+        Ok(Program::parse_with(&toks, &self.parse_options)?)
+    }
+
+    ///
+    /// The last statement of `program`, an input without any is an empty expression
+    ///
+    fn last_statement<'a>(program : &'a Program, input : &str) -> Result<&'a Statement, CalcError>{
+        // Nothing but blanks and comments
+        program.statements.last().ok_or_else(|| {
+            let end = Position::end_of(input);
+            let err = ArithmeticParserError::EmptyExpression(Span::new(end, end));
+            CalcError::Parse(ParserError::Arithmetic(err))
+        })
+    }
+
+    fn execute_program(&mut self, program : &Program) -> Result<Vec<Value>, CalcError>{
+        self.evaluator.eval_program(program).map_err(|e| Self::failure(program, e))
+    }
+
+    ///
+    /// A failure of `program`, which tells the failing statement when there are several
+    ///
+    fn failure(program : &Program, e : ProgramError) -> CalcError{
+        // A lone statement is the whole input
+        if program.statements.len() == 1 { CalcError::Arithmetic(*e.error) } else { CalcError::Program(e) }
+    }
+
+    pub fn parse_options(&self) -> &ParseOptions{
+        &self.parse_options
+    }
+
+    ///
+    /// Change how the following inputs are parsed, e.g to disable implicit multiplication
+    ///
+    pub fn set_parse_options(&mut self, options : ParseOptions){
+        self.parse_options = options;
+    }
+
+    pub fn evaluator(&self) -> &Evaluator{
+        &self.evaluator
+    }
+
+    pub fn evaluator_mut(&mut self) -> &mut Evaluator{
+        &mut self.evaluator
+    }
+}
@@ -0,0 +1,534 @@
+//!
+//! Functions of arbitrary precision decimals, used in [`NumericMode::Decimal`](crate::evaluator::NumericMode::Decimal)
+//!
+//! Each function takes the context giving the precision and rounding of its result.
+//! The computation itself is done with a few more digits, and rounded once at the end
+//!
+
+use std::num::NonZeroU64;
+
+use bigdecimal::{BigDecimal, Context, RoundingMode};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::value::MAX_EXACT_EXPONENT;
+
+/// Digits computed beyond the precision of the result, to absorb the error of intermediate steps
+const GUARD_DIGITS : u64 = 10;
+
+/// Largest magnitude of an argument to `exp`, beyond it the result has too many digits in its exponent
+const MAX_EXP_ARGUMENT : f64 = 1e12;
+
+/// Most integer digits of an argument to `sin`, `cos` and `tan`, reducing it to a turn takes as many digits of π
+const MAX_ANGLE_DIGITS : i64 = 1000;
+
+/// Most iterations of the series and of the Newton steps, reached only when precision is lost to cancellation
+const MAX_ITERATIONS : usize = 100_000;
+
+///
+/// The context intermediate results are computed in
+///
+fn working(ctx : &Context) -> Context{
+    guarded(ctx, GUARD_DIGITS)
+}
+
+fn guarded(ctx : &Context, extra : u64) -> Context{
+    Context::new(NonZeroU64::new(ctx.precision().get() + extra).expect("precision is not zero"), RoundingMode::HalfEven)
+}
+
+///
+/// `10^-digits`, the relative error a result of `digits` digits may have
+///
+fn epsilon(digits : u64) -> BigDecimal{
+    BigDecimal::new(BigInt::one(), digits as i64)
+}
+
+///
+/// Whether `term` no longer changes `sum` at the precision of `w`
+///
+fn negligible(term : &BigDecimal, sum : &BigDecimal, w : &Context) -> bool{
+    let digits = w.precision().get();
+    term.is_zero() || term.abs() <= sum.abs() * epsilon(digits) || term.abs() <= epsilon(2 * digits)
+}
+
+///
+/// Round `x` to `ctx`, dropping trailing zeros
+///
+pub(crate) fn round(x : BigDecimal, ctx : &Context) -> BigDecimal{
+    ctx.round_decimal(x).normalized()
+}
+
+///
+/// Round a result computed with guard digits to `ctx`
+///
+/// A result within a few units of the guard digits of a number of the target precision is taken to be
+/// that number, so that exact results such as `nrt(3, 27)` are not pushed off by a directed rounding mode
+///
+fn finish(x : BigDecimal, ctx : &Context) -> BigDecimal{
+    // A zero keeps the scale of its computation, which would be written out
+    if x.is_zero(){
+        return BigDecimal::zero();
+    }
+    let nearest = x.with_precision_round(ctx.precision(), RoundingMode::HalfEven);
+    let ulp_exponent = nearest.order_of_magnitude() - ctx.precision().get() as i64 + 1;
+    let tolerance = BigDecimal::new(BigInt::one(), (GUARD_DIGITS as i64 - 2) - ulp_exponent);
+    if (&x - &nearest).abs() <= tolerance{
+        return nearest.normalized();
+    }
+    round(x, ctx)
+}
+
+///
+/// Compute `f` with guard digits, then round the result to `ctx`
+///
+fn guard(ctx : &Context, f : impl FnOnce(&Context) -> BigDecimal) -> BigDecimal{
+    finish(f(&working(ctx)), ctx)
+}
+
+pub(crate) fn from_rational(r : &BigRational, ctx : &Context) -> BigDecimal{
+    div(&BigDecimal::from(r.numer().clone()), &BigDecimal::from(r.denom().clone()), ctx)
+}
+
+///
+/// Whether the exponent of `x` is within [`MAX_EXACT_EXPONENT`], the zeros between the exponents
+/// of decimals are written out to add or compare them
+///
+pub(crate) fn in_range(x : &BigDecimal) -> bool{
+    x.order_of_magnitude().unsigned_abs() <= MAX_EXACT_EXPONENT
+}
+
+///
+/// The exact value of `x` as a fraction, `None` if its exponent is beyond [`MAX_EXACT_EXPONENT`]
+///
+pub(crate) fn to_rational(x : &BigDecimal) -> Option<BigRational>{
+    if !in_range(x){
+        return None;
+    }
+    let (digits, scale) = x.as_bigint_and_exponent();
+    let power = num_traits::pow(BigInt::from(10), scale.unsigned_abs() as usize);
+    Some(if scale >= 0{
+        BigRational::new(digits, power)
+    }
+    else{
+        BigRational::from_integer(digits * power)
+    })
+}
+
+fn half() -> BigDecimal{
+    BigDecimal::new(5.into(), 1)
+}
+
+///
+/// `x / n`, cheaper than [`quotient`] for the small divisors of series terms
+///
+fn divide_small(x : &BigDecimal, n : u64, w : &Context) -> BigDecimal{
+    let (digits, scale) = x.as_bigint_and_exponent();
+    let shift = w.precision().get() + 2;
+    let shifted = digits * num_traits::pow(BigInt::from(10), shift as usize);
+    w.round_decimal(BigDecimal::new(shifted / n, scale + shift as i64))
+}
+
+fn quotient(x : &BigDecimal, y : &BigDecimal, w : &Context) -> BigDecimal{
+    w.round_decimal(x * y.inverse_with_context(w))
+}
+
+///
+/// `x / y`, `y` must not be zero
+///
+pub(crate) fn div(x : &BigDecimal, y : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| quotient(x, y, w))
+}
+
+///
+/// `x^e`, `x` must not be zero when `e` is negative
+///
+pub(crate) fn powi(x : &BigDecimal, e : i64, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| x.powi_with_context(e, w))
+}
+
+///
+/// `x^y` for a positive `x`, `None` if the result is too large to represent
+///
+pub(crate) fn powf(x : &BigDecimal, y : &BigDecimal, ctx : &Context) -> Option<BigDecimal>{
+    let w = working(ctx);
+    let exponent = w.round_decimal(y * ln_at(x, &guarded(ctx, 2 * GUARD_DIGITS)));
+    Some(finish(exp_at(&exponent, &w)?, ctx))
+}
+
+///
+/// `e^x` by its series on `x / 2^k`, squared `k` times. `k` grows with the precision,
+/// so that the series needs few terms
+///
+fn exp_at(x : &BigDecimal, w : &Context) -> Option<BigDecimal>{
+    if x.is_zero(){
+        return Some(BigDecimal::one());
+    }
+    let magnitude = to_f64(&x.abs());
+    if magnitude > MAX_EXP_ARGUMENT{
+        return None;
+    }
+
+    let halvings = magnitude.log2().ceil().max(0.0) as u32 + (w.precision().get() as f64).sqrt() as u32;
+    // Each squaring doubles the relative error
+    let wp = guarded(w, u64::from(halvings) * 3 / 10 + 2);
+    let reduced = wp.round_decimal(x * BigDecimal::new(num_traits::pow(BigInt::from(5), halvings as usize), halvings.into()));
+
+    let (mut term, mut sum) = (BigDecimal::one(), BigDecimal::one());
+    for n in 1..MAX_ITERATIONS as u64{
+        term = divide_small(&(term * &reduced), n, &wp);
+        sum = wp.round_decimal(sum + &term);
+        if negligible(&term, &sum, &wp){
+            break;
+        }
+    }
+    for _ in 0..halvings{
+        sum = wp.round_decimal(&sum * &sum);
+    }
+    Some(w.round_decimal(sum))
+}
+
+///
+/// `e^x`, `None` if the result is too large or too small to represent
+///
+pub(crate) fn exp(x : &BigDecimal, ctx : &Context) -> Option<BigDecimal>{
+    Some(finish(exp_at(x, &working(ctx))?, ctx))
+}
+
+///
+/// Natural logarithm of a positive `x`, by Halley's iteration on `exp` from a float estimate
+///
+fn ln_at(x : &BigDecimal, w : &Context) -> BigDecimal{
+    if x.is_one(){
+        return BigDecimal::zero();
+    }
+
+    // x = digits * 10^-scale, the float estimate is taken on the leading digits so it cannot overflow
+    let (digits, scale) = x.as_bigint_and_exponent();
+    let digits = digits.to_string();
+    let leading = format!("0.{}", &digits[..digits.len().min(17)]).parse::<f64>().unwrap_or(1.0);
+    let magnitude = digits.len() as f64 - scale as f64;
+    let mut y = BigDecimal::try_from(leading.ln() + magnitude * std::f64::consts::LN_10).unwrap_or_default();
+
+    // Once the steps stop shrinking they are rounding noise
+    let two = BigDecimal::from(2);
+    let mut previous : Option<BigDecimal> = None;
+    for _ in 0..MAX_ITERATIONS{
+        let e = exp_at(&y, w).expect("the logarithm of a decimal is in range");
+        let step = quotient(&(&two * (x - &e)), &(x + &e), w);
+        let size = step.abs();
+        if previous.as_ref().is_some_and(|p| &size >= p){
+            break;
+        }
+        y = w.round_decimal(&y + &step);
+        if negligible(&step, &y, w){
+            break;
+        }
+        previous = Some(size);
+    }
+    y
+}
+
+pub(crate) fn ln(x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| ln_at(x, w))
+}
+
+///
+/// Logarithm of a positive `x` in a positive `base` other than 1
+///
+pub(crate) fn log(base : &BigDecimal, x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| quotient(&ln_at(x, w), &ln_at(base, w), w))
+}
+
+///
+/// Arctangent of a small `x` by its series
+///
+fn atan_series(x : &BigDecimal, w : &Context) -> BigDecimal{
+    let square = w.round_decimal(x * x);
+    let (mut power, mut sum) = (x.clone(), x.clone());
+    for k in 1..MAX_ITERATIONS{
+        power = w.round_decimal(-(power * &square));
+        let term = divide_small(&power, 2 * k as u64 + 1, w);
+        sum = w.round_decimal(sum + &term);
+        if negligible(&term, &sum, w){
+            break;
+        }
+    }
+    sum
+}
+
+///
+/// π by Machin's formula
+///
+fn pi_at(w : &Context) -> BigDecimal{
+    let fifth = atan_series(&BigDecimal::new(2.into(), 1), w);
+    let other = atan_series(&BigDecimal::from(239).inverse_with_context(w), w);
+    w.round_decimal(BigDecimal::from(16) * fifth - BigDecimal::from(4) * other)
+}
+
+pub(crate) fn pi(ctx : &Context) -> BigDecimal{
+    guard(ctx, pi_at)
+}
+
+pub(crate) fn e(ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| exp_at(&BigDecimal::one(), w).expect("1 is in range"))
+}
+
+fn atan_at(x : &BigDecimal, w : &Context) -> BigDecimal{
+    if x.is_zero(){
+        return BigDecimal::zero();
+    }
+    if x.abs() > BigDecimal::one(){
+        // atan(x) = ±π/2 - atan(1/x)
+        let half_pi = w.round_decimal(pi_at(w) * half());
+        let rest = atan_at(&x.inverse_with_context(w), w);
+        let half_pi = if x.is_negative() { -half_pi } else { half_pi };
+        return w.round_decimal(half_pi - rest);
+    }
+
+    // Halve the angle until the series converges quickly, atan(x) = 2 atan(x / (1 + sqrt(1 + x²)))
+    let (mut x, mut doublings) = (x.clone(), 0u32);
+    let limit = BigDecimal::new(125.into(), 3);
+    while x.abs() > limit{
+        let root = (BigDecimal::one() + &x * &x).sqrt_with_context(w).expect("1 + x² is positive");
+        x = quotient(&x, &(BigDecimal::one() + root), w);
+        doublings += 1;
+    }
+    w.round_decimal(atan_series(&x, w) * BigDecimal::from(1u64 << doublings))
+}
+
+pub(crate) fn atan(x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| atan_at(x, w))
+}
+
+///
+/// Angle of the point `(x, y)`, between -π and π
+///
+pub(crate) fn atan2(y : &BigDecimal, x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| {
+        if x.is_zero(){
+            let half_pi = w.round_decimal(pi_at(w) * half());
+            return match y.sign(){
+                num_bigint::Sign::Plus => half_pi,
+                num_bigint::Sign::Minus => -half_pi,
+                num_bigint::Sign::NoSign => BigDecimal::zero()
+            };
+        }
+        let angle = atan_at(&quotient(y, x, w), w);
+        if x.is_positive(){
+            angle
+        }
+        else if y.is_negative(){
+            w.round_decimal(angle - pi_at(w))
+        }
+        else{
+            w.round_decimal(angle + pi_at(w))
+        }
+    })
+}
+
+///
+/// `asin(x)` for `|x| <= 1`
+///
+pub(crate) fn asin(x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| asin_at(x, w))
+}
+
+fn asin_at(x : &BigDecimal, w : &Context) -> BigDecimal{
+    if x.abs().is_one(){
+        let half_pi = w.round_decimal(pi_at(w) * half());
+        return if x.is_negative() { -half_pi } else { half_pi };
+    }
+    let cos = (BigDecimal::one() - x * x).sqrt_with_context(w).expect("1 - x² is positive");
+    atan_at(&quotient(x, &cos, w), w)
+}
+
+///
+/// `acos(x)` for `|x| <= 1`
+///
+pub(crate) fn acos(x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| w.round_decimal(pi_at(w) * half() - asin_at(x, w)))
+}
+
+///
+/// `x` reduced to between -π and π, computed with enough digits for the integer part of `x`
+///
+fn reduce_angle(x : &BigDecimal, w : &Context) -> BigDecimal{
+    let integer_digits = x.order_of_magnitude().max(0) as u64;
+    let w = guarded(w, integer_digits);
+    let turn = pi_at(&w) * BigDecimal::from(2);
+    let turns = quotient(x, &turn, &w).with_scale_round(0, RoundingMode::HalfEven);
+    w.round_decimal(x - turns * turn)
+}
+
+///
+/// Sum of the series `sum(±x^(2k + start) / (2k + start)!)` giving `sin` or `cos`
+///
+fn trig_series(x : &BigDecimal, start : u64, w : &Context) -> BigDecimal{
+    let square = w.round_decimal(x * x);
+    let mut term = if start == 0 { BigDecimal::one() } else { x.clone() };
+    let mut sum = term.clone();
+    for k in 1..MAX_ITERATIONS as u64{
+        let n = 2 * k + start;
+        term = divide_small(&-(term * &square), (n - 1) * n, w);
+        sum = w.round_decimal(sum + &term);
+        if negligible(&term, &sum, w){
+            break;
+        }
+    }
+    sum
+}
+
+fn sin_at(x : &BigDecimal, w : &Context) -> BigDecimal{
+    trig_series(&reduce_angle(x, w), 1, w)
+}
+
+fn cos_at(x : &BigDecimal, w : &Context) -> BigDecimal{
+    trig_series(&reduce_angle(x, w), 0, w)
+}
+
+///
+/// Whether `x` is small enough for `sin`, `cos` and `tan`, see [`MAX_ANGLE_DIGITS`]
+///
+fn reducible(x : &BigDecimal) -> bool{
+    x.order_of_magnitude() < MAX_ANGLE_DIGITS
+}
+
+///
+/// `sin(x)`, `None` if `x` is too large to reduce to a turn
+///
+pub(crate) fn sin(x : &BigDecimal, ctx : &Context) -> Option<BigDecimal>{
+    reducible(x).then(|| guard(ctx, |w| sin_at(x, w)))
+}
+
+pub(crate) fn cos(x : &BigDecimal, ctx : &Context) -> Option<BigDecimal>{
+    reducible(x).then(|| guard(ctx, |w| cos_at(x, w)))
+}
+
+pub(crate) fn tan(x : &BigDecimal, ctx : &Context) -> Option<BigDecimal>{
+    reducible(x).then(|| guard(ctx, |w| quotient(&sin_at(x, w), &cos_at(x, w), w)))
+}
+
+///
+/// `e^x` and `e^-x`, `None` if they are too large to represent
+///
+fn exp_pair(x : &BigDecimal, w : &Context) -> Option<(BigDecimal, BigDecimal)>{
+    let e = exp_at(x, w)?;
+    let inverse = e.inverse_with_context(w);
+    Some((e, inverse))
+}
+
+pub(crate) fn sinh(x : &BigDecimal, ctx : &Context) -> Option<BigDecimal>{
+    let w = working(ctx);
+    let (e, inverse) = exp_pair(x, &w)?;
+    Some(finish((e - inverse) * half(), ctx))
+}
+
+pub(crate) fn cosh(x : &BigDecimal, ctx : &Context) -> Option<BigDecimal>{
+    let w = working(ctx);
+    let (e, inverse) = exp_pair(x, &w)?;
+    Some(finish((e + inverse) * half(), ctx))
+}
+
+pub(crate) fn tanh(x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    // Far from zero the result is ±1 at any precision this supports
+    let Some((e, inverse)) = exp_pair(x, &working(ctx)) else{
+        return if x.is_negative() { -BigDecimal::one() } else { BigDecimal::one() };
+    };
+    guard(ctx, |w| quotient(&(&e - &inverse), &(&e + &inverse), w))
+}
+
+pub(crate) fn asinh(x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    // Computed on |x| so that x + sqrt(x² + 1) does not cancel
+    let result = guard(ctx, |w| {
+        let x = x.abs();
+        let root = (BigDecimal::one() + &x * &x).sqrt_with_context(w).expect("x² + 1 is positive");
+        ln_at(&w.round_decimal(x + root), w)
+    });
+    if x.is_negative() { -result } else { result }
+}
+
+///
+/// `acosh(x)` for `x >= 1`
+///
+pub(crate) fn acosh(x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| {
+        let root = (x * x - BigDecimal::one()).sqrt_with_context(w).expect("x² - 1 is not negative");
+        ln_at(&w.round_decimal(x + root), w)
+    })
+}
+
+///
+/// `atanh(x)` for `|x| < 1`
+///
+pub(crate) fn atanh(x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| {
+        let ratio = quotient(&(BigDecimal::one() + x), &(BigDecimal::one() - x), w);
+        w.round_decimal(ln_at(&ratio, w) * half())
+    })
+}
+
+///
+/// Square root of a non negative `x`
+///
+pub(crate) fn sqrt(x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| x.sqrt_with_context(w).expect("x is not negative"))
+}
+
+pub(crate) fn cbrt(x : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| x.cbrt_with_context(w))
+}
+
+///
+/// `n`th root of `x`, `n` must not be zero and `x` must only be negative for odd integer roots
+///
+pub(crate) fn nrt(n : &BigDecimal, x : &BigDecimal, ctx : &Context) -> Option<BigDecimal>{
+    if x.is_zero(){
+        return n.is_positive().then(BigDecimal::zero);
+    }
+    let w = working(ctx);
+    let exponent = n.inverse_with_context(&guarded(ctx, 2 * GUARD_DIGITS));
+    let root = powf(&x.abs(), &exponent, &w)?;
+    let root = finish(root, ctx);
+    Some(if x.is_negative() { -root } else { root })
+}
+
+pub(crate) fn hypot(x : &BigDecimal, y : &BigDecimal, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| (x * x + y * y).sqrt_with_context(w).expect("x² + y² is not negative"))
+}
+
+///
+/// `r π^power`, `power` being 1 or -1, the factor between radians and other units of angle
+///
+pub(crate) fn times_pi(r : &BigRational, power : i32, ctx : &Context) -> BigDecimal{
+    guard(ctx, |w| {
+        let r = from_rational(r, w);
+        if power < 0 { quotient(&r, &pi_at(w), w) } else { w.round_decimal(r * pi_at(w)) }
+    })
+}
+
+///
+/// Round `x` to an integer with `mode`
+///
+pub(crate) fn to_integer(x : &BigDecimal, mode : RoundingMode) -> BigDecimal{
+    // Without fractional digits there is nothing to round, and a scale of 0 would write out the zeros of the exponent
+    if x.fractional_digit_count() <= 0{
+        return x.normalized();
+    }
+    x.with_scale_round(0, mode).normalized()
+}
+
+///
+/// Whether `x` is an odd integer
+///
+pub(crate) fn is_odd_integer(x : &BigDecimal) -> bool{
+    x.is_integer() && x.with_scale(0).as_bigint_and_exponent().0.is_odd()
+}
+
+///
+/// The value of `x` as a float, rounded
+///
+pub(crate) fn to_f64(x : &BigDecimal) -> f64{
+    x.to_f64().unwrap_or(f64::NAN)
+}
@@ -0,0 +1,102 @@
+use crate::evaluator::{ArithmeticError, ProgramError};
+use crate::lexer::{LexError, Span};
+use crate::statement::ParserError;
+
+///
+/// Any failure that can occur while turning source text into a value
+///
+/// Every error points at the offending part of the source through its [`Span`],
+/// and has a stable code that does not change when the message wording does
+///
+/// | Range | Stage      |
+/// |-------|------------|
+/// | E1xx  | Lexing     |
+/// | E2xx  | Parsing    |
+/// | E3xx  | Evaluation |
+///
+#[derive(Debug)]
+pub enum CalcError{
+    Lex(LexError),
+    Parse(ParserError),
+    Arithmetic(ArithmeticError),
+    /// A statement of an input holding several failed, the error tells which
+    Program(ProgramError)
+}
+
+impl CalcError{
+    pub fn span(&self) -> &Span{
+        match self{
+            Self::Lex(e) => e.span(),
+            Self::Parse(e) => e.span(),
+            Self::Arithmetic(e) => e.span(),
+            Self::Program(e) => e.span()
+        }
+    }
+
+    pub fn code(&self) -> &'static str{
+        match self{
+            Self::Lex(e) => e.code(),
+            Self::Parse(e) => e.code(),
+            Self::Arithmetic(e) => e.code(),
+            Self::Program(e) => e.code()
+        }
+    }
+
+    pub fn message(&self) -> String{
+        match self{
+            Self::Lex(e) => e.message(),
+            Self::Parse(e) => e.message(),
+            Self::Arithmetic(e) => e.message(),
+            Self::Program(e) => e.message()
+        }
+    }
+
+    ///
+    /// Render the source with the error location underlined
+    ///
+    pub fn contextualize(&self, source : &str) -> String{
+        let msg = format!("[{}] {}", self.code(), self.message());
+        self.span().underline(source, Some(&msg))
+    }
+}
+
+impl std::fmt::Display for CalcError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "error[{}]: {}", self.code(), self.message())
+    }
+}
+
+impl std::error::Error for CalcError{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>{
+        match self{
+            Self::Lex(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Arithmetic(e) => Some(e),
+            Self::Program(e) => Some(e)
+        }
+    }
+}
+
+impl From<LexError> for CalcError{
+    fn from(e : LexError) -> Self{
+        Self::Lex(e)
+    }
+}
+
+impl From<ParserError> for CalcError{
+    fn from(e : ParserError) -> Self{
+        Self::Parse(e)
+    }
+}
+
+impl From<ProgramError> for CalcError{
+    fn from(e : ProgramError) -> Self{
+        Self::Program(e)
+    }
+}
+
+impl From<ArithmeticError> for CalcError{
+    fn from(e : ArithmeticError) -> Self{
+        Self::Arithmetic(e)
+    }
+}
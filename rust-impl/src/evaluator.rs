@@ -1,7 +1,224 @@
-use crate::lexer::Span;
+use std::num::NonZeroU64;
+
+use crate::angle;
+use crate::decimal;
+use crate::expression::{ArithmeticNode, BinOp};
+use crate::format::Format;
+use crate::functions::{Arity, CallContext, FunctionError, FunctionRegistry, UserFunction};
+use crate::interval::Interval;
+use crate::lexer::{NumberLiteral, Span};
+use crate::statement::Statement;
+use crate::units::{self, UnitRegistry};
+pub use crate::angle::AngleMode;
+pub use crate::value::Value;
+use crate::value::OperationError;
+pub use bigdecimal::RoundingMode;
+
+/// Deepest nesting of user function calls, so that runaway recursion
+/// is reported instead of running forever
+const MAX_CALL_DEPTH : usize = 1000;
+
+/// Stack left below which evaluating a node allocates a new stack segment,
+/// so recursion is only bounded by `MAX_CALL_DEPTH`
+const STACK_RED_ZONE : usize = 64 * 1024;
+const STACK_SEGMENT_SIZE : usize = 1024 * 1024;
+
+///
+/// How number literals are read, and so which arithmetic applies to them
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumericMode{
+    /// 64 bit floating point numbers
+    #[default]
+    Float,
+    /// Integers of arbitrary size. Arithmetic stays exact while its result is an integer,
+    /// other literals and results, e.g `1 / 3` or `2 ^ -1`, fall back to floats
+    Integer,
+    /// Exact fractions of arbitrary size. Arithmetic and integer powers stay exact,
+    /// other operations fall back to floats, which [`Value::is_exact`] reports
+    Rational,
+    /// Decimals of `precision` significant digits, a precision of 0 is taken as 1.
+    /// Literals, operators and built-in functions all round their result with `rounding`
+    Decimal{
+        precision : u32,
+        rounding : RoundingMode
+    },
+    /// Floats extended to complex numbers, with the imaginary unit `i`. Powers and functions
+    /// of real numbers outside of their real domain, e.g `sqrt(-4)`, give complex results
+    Complex,
+    /// Intervals of floats with their bounds rounded outward, so that results are guaranteed to hold
+    /// every value the expression takes for numbers in its intervals. Literals are the narrowest
+    /// intervals holding their exact value, e.g `0.1` lies between two floats
+    Interval
+}
+
+impl NumericMode{
+    /// Precision of decimals met outside of decimal mode, that of IEEE 754 decimal128
+    pub const DEFAULT_DECIMAL_PRECISION : u32 = 34;
+
+    ///
+    /// Decimals of `precision` significant digits, rounded half to even
+    ///
+    pub fn decimal(precision : u32) -> Self{
+        Self::Decimal{ precision, rounding: RoundingMode::HalfEven }
+    }
+
+    ///
+    /// The precision and rounding decimals are computed with in this mode
+    ///
+    pub(crate) fn decimal_context(&self) -> bigdecimal::Context{
+        let (precision, rounding) = match *self{
+            Self::Decimal { precision, rounding } => (precision, rounding),
+            _ => (Self::DEFAULT_DECIMAL_PRECISION, RoundingMode::HalfEven)
+        };
+        bigdecimal::Context::new(NonZeroU64::new(precision.max(1).into()).expect("precision is not zero"), rounding)
+    }
+}
+
+///
+/// What running a statement did
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatementOutcome{
+    /// `name = expr`, with the value assigned to `name`
+    Assigned{
+        name : String,
+        value : Value
+    },
+    /// `name(params) = body`
+    FunctionDefined{
+        name : String,
+        params : Vec<String>
+    },
+    /// `unit name = expr`
+    UnitDefined{
+        name : String
+    },
+    /// An expression on its own, with its value
+    Value(Value)
+}
+
+impl StatementOutcome{
+    ///
+    /// The value of the statement, for an assignment that is the assigned value. Definitions have none
+    ///
+    pub fn value(&self) -> Option<&Value>{
+        match self{
+            Self::Assigned { value, .. } | Self::Value(value) => Some(value),
+            Self::FunctionDefined { .. } | Self::UnitDefined { .. } => None
+        }
+    }
+
+    pub fn into_value(self) -> Option<Value>{
+        match self{
+            Self::Assigned { value, .. } | Self::Value(value) => Some(value),
+            Self::FunctionDefined { .. } | Self::UnitDefined { .. } => None
+        }
+    }
+
+    ///
+    /// Describe the outcome for a trace, with values written with `format`, e.g `Setting 'x' = 2`
+    ///
+    pub fn describe(&self, format : &Format) -> String{
+        match self{
+            Self::Assigned { name, value } => format!("Setting '{name}' = {}", value.format(format)),
+            Self::FunctionDefined { name, params } => format!("Defining '{name}({})'", params.join(", ")),
+            Self::UnitDefined { name } => format!("Defining unit '{name}'"),
+            Self::Value(value) => format!("Expression resolves to {}", value.format(format))
+        }
+    }
+}
+
+impl std::fmt::Display for StatementOutcome{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "{}", self.describe(&Format::default()))
+    }
+}
+
+///
+/// Told about each statement an [`Evaluator`] runs, e.g to trace them, see [`Evaluator::set_observer`]
+///
+/// Closures taking the statement and its outcome are observers
+///
+/// Observers are `Send` so that an [`Evaluator`] can be moved to another thread, they are only called
+/// through `&mut` so they need not be `Sync`
+///
+pub trait Observer : Send{
+    fn statement(&mut self, stmnt : &Statement, outcome : &StatementOutcome);
+
+    ///
+    /// The statement failed, it may have been partly applied
+    ///
+    fn error(&mut self, _stmnt : &Statement, _error : &ArithmeticError){}
+}
+
+impl<F> Observer for F
+    where F : FnMut(&Statement, &StatementOutcome) + Send
+{
+    fn statement(&mut self, stmnt : &Statement, outcome : &StatementOutcome){
+        self(stmnt, outcome)
+    }
+}
 
 pub struct Evaluator{
-    variables : std::collections::HashMap<String, f64>
+    variables : std::collections::HashMap<String, Value>,
+    numeric_mode : NumericMode,
+    angle_mode : AngleMode,
+    format : Format,
+    functions : FunctionRegistry,
+    user_functions : std::collections::HashMap<String, UserFunction>,
+    units : UnitRegistry,
+    observer : Option<Box<dyn Observer>>
+}
+
+///
+/// The parameters bound while evaluating the body of a user function
+///
+#[derive(Default)]
+struct Scope<'a>{
+    params : &'a [String],
+    args : &'a [Value],
+    /// Count of user function calls being evaluated
+    depth : usize,
+    /// Whether names that are not variables resolve to units, see [`Evaluator::lookup`]
+    units : bool
+}
+
+impl<'a> Scope<'a>{
+    fn get(&self, name : &str) -> Option<Value>{
+        self.params.iter().position(|p| p == name).map(|i| self.args[i].clone())
+    }
+
+    ///
+    /// The scope of the left or right operand of `expr`
+    ///
+    /// Units are resolved right after a value, as in `5 m`, after `*`, `/` or `^` that follow
+    /// such a unit, as in `72 km/h`, and anywhere in the target of `to`
+    ///
+    fn operand(&self, expr : &ArithmeticNode, right : bool) -> Scope<'a>{
+        use crate::expression::ArithmeticNodeKind;
+        let units = match (&expr.kind, right){
+            (ArithmeticNodeKind::Mul(..) | ArithmeticNodeKind::Div(..) | ArithmeticNodeKind::Pow(..), false) => self.units,
+            (ArithmeticNodeKind::Mul(a, _) | ArithmeticNodeKind::Div(a, _), true) => self.units || expr.juxtaposed || carries_unit(a),
+            (ArithmeticNodeKind::Convert(..), true) => true,
+            _ => false
+        };
+        Scope{ units, ..*self }
+    }
+}
+
+///
+/// Whether `node` ends in a unit written after a value, as `5 m` and `5 m^2 * kg` do
+///
+fn carries_unit(mut node : &ArithmeticNode) -> bool{
+    use crate::expression::ArithmeticNodeKind;
+    loop{
+        match &node.kind{
+            ArithmeticNodeKind::Mul(..) if node.juxtaposed => return true,
+            ArithmeticNodeKind::Mul(a, _) | ArithmeticNodeKind::Div(a, _) | ArithmeticNodeKind::Pow(a, _) => node = a,
+            _ => return false
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -9,7 +226,47 @@ pub enum ArithmeticError{
     DivideByZero(Span),
     InfinityDivision(Span),
     LogZero(Span),
-    UndefinedVariable(String, Span)
+    UndefinedVariable(String, Span),
+    UndefinedFunction(String, Span),
+    InvalidArgumentCount{
+        function : String,
+        expected : Arity,
+        got : usize,
+        span : Span
+    },
+    /// A function was called with an argument outside of its domain
+    Domain{
+        function : String,
+        message : String,
+        span : Span
+    },
+    /// An operation was applied to a value of the wrong type
+    Type{
+        message : String,
+        span : Span
+    },
+    /// An operation mixes quantities of different dimensions, or a unit definition is invalid
+    Unit{
+        message : String,
+        span : Span
+    },
+    /// An operation on intervals has no bounds, e.g a division by an interval holding zero, or an interval is empty
+    Interval{
+        message : String,
+        span : Span
+    },
+    /// User functions called each other deeper than the evaluator allows
+    RecursionLimit{
+        function : String,
+        span : Span
+    },
+    /// Evaluating the body of a user function failed, the span is the call
+    /// made from the evaluated statement
+    InFunction{
+        function : String,
+        span : Span,
+        error : Box<ArithmeticError>
+    }
 }
 
 impl ArithmeticError{
@@ -18,7 +275,32 @@ impl ArithmeticError{
             Self::DivideByZero(span)
             | Self::InfinityDivision(span)
             | Self::LogZero(span)
-            | Self::UndefinedVariable(_, span) => span
+            | Self::UndefinedVariable(_, span)
+            | Self::UndefinedFunction(_, span)
+            | Self::InvalidArgumentCount { span, .. }
+            | Self::Domain { span, .. }
+            | Self::Type { span, .. }
+            | Self::Unit { span, .. }
+            | Self::Interval { span, .. }
+            | Self::RecursionLimit { span, .. }
+            | Self::InFunction { span, .. } => span
+        }
+    }
+
+    pub fn code(&self) -> &'static str{
+        match self{
+            Self::DivideByZero(_) => "E301",
+            Self::InfinityDivision(_) => "E302",
+            Self::LogZero(_) => "E303",
+            Self::UndefinedVariable(..) => "E304",
+            Self::UndefinedFunction(..) => "E305",
+            Self::InvalidArgumentCount { .. } => "E306",
+            Self::Domain { .. } => "E307",
+            Self::RecursionLimit { .. } => "E308",
+            Self::Type { .. } => "E309",
+            Self::Unit { .. } => "E310",
+            Self::Interval { .. } => "E311",
+            Self::InFunction { error, .. } => error.code()
         }
     }
 
@@ -30,95 +312,581 @@ impl ArithmeticError{
             Self::DivideByZero(_) => "Division by Zero".into(),
             Self::InfinityDivision(_) => "Undefined Infinite Division".into(),
             Self::LogZero(_) => "Logarithm of Zero".into(),
-            Self::UndefinedVariable(name, _) => format!("Undefined variable: {name:?}")
+            Self::UndefinedVariable(name, _) => format!("Undefined variable: {name:?}"),
+            Self::UndefinedFunction(name, _) => format!("Undefined function: {name:?}"),
+            Self::InvalidArgumentCount { function, expected, got, .. } => {
+                format!("{function:?} expects {expected} argument(s), got {got}")
+            },
+            Self::Domain { function, message, .. } => format!("Invalid argument to {function:?}: {message}"),
+            Self::RecursionLimit { function, .. } => format!("Recursion too deep in {function:?}"),
+            Self::Type { message, .. } | Self::Unit { message, .. } | Self::Interval { message, .. } => message.clone(),
+            Self::InFunction { function, error, .. } => format!("In call to {function:?}: {}", error.message())
         }
     }
 }
 
+impl std::fmt::Display for ArithmeticError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ArithmeticError{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>{
+        match self{
+            Self::InFunction { error, .. } => Some(error.as_ref()),
+            _ => None
+        }
+    }
+}
+
+///
+/// A failure while running a [`Program`](crate::program::Program)
+///
+#[derive(Debug)]
+pub struct ProgramError{
+    /// Index of the failing statement in the program
+    pub index : usize,
+    /// Span of the whole failing statement
+    pub statement : Span,
+    pub error : Box<ArithmeticError>
+}
+
+impl ProgramError{
+    pub fn span(&self) -> &Span{
+        self.error.span()
+    }
+
+    pub fn code(&self) -> &'static str{
+        self.error.code()
+    }
+
+    pub fn message(&self) -> String{
+        format!("In statement {}: {}", self.index + 1, self.error.message())
+    }
+
+    pub fn contextualize(&self, source : &str) -> String{
+        self.span().underline(source, Some(&self.message()))
+    }
+}
+
+impl std::fmt::Display for ProgramError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ProgramError{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>{
+        Some(self.error.as_ref())
+    }
+}
+
+///
+/// Describe an operator applied to operands it does not support, `l` and `r` describe the operands
+///
+fn mismatch_message(op : BinOp, l : &str, r : &str) -> String{
+    match op{
+        BinOp::Add => format!("Cannot add {r} to {l}"),
+        BinOp::Sub => format!("Cannot subtract {r} from {l}"),
+        BinOp::Mul => format!("Cannot multiply {l} by {r}"),
+        BinOp::Div | BinOp::IntDiv => format!("Cannot divide {l} by {r}"),
+        BinOp::Mod => format!("Cannot take the remainder of {l} divided by {r}"),
+        BinOp::Exp => format!("Cannot raise {l} to the power of {r}"),
+        BinOp::To => format!("Cannot convert {l} to {r}"),
+        BinOp::PlusMinus => format!("Cannot give {l} an uncertainty of {r}"),
+        _ => format!("Cannot compare {l} with {r} using {:?}", op.symbol())
+    }
+}
+
+impl Default for Evaluator{
+    fn default() -> Self{
+        Self::new()
+    }
+}
+
 impl Evaluator{
     pub fn new() -> Self{
         let mut vars = std::collections::HashMap::new();
-        vars.insert("PI".to_string(), std::f64::consts::PI);
-        vars.insert("E".to_string(), std::f64::consts::E);
-        vars.insert("INF".to_string(), std::f64::INFINITY);
+        vars.insert("PI".to_string(), Value::Number(std::f64::consts::PI));
+        vars.insert("E".to_string(), Value::Number(std::f64::consts::E));
+        vars.insert("INF".to_string(), Value::Number(f64::INFINITY));
         Self{
-            variables: vars
+            variables: vars,
+            numeric_mode: NumericMode::default(),
+            angle_mode: AngleMode::default(),
+            format: Format::default(),
+            functions: FunctionRegistry::with_builtins(),
+            user_functions: std::collections::HashMap::new(),
+            units: UnitRegistry::with_builtins(),
+            observer: None
         }
     }
 
-    pub fn get_variable(&self, name : &str) -> Option<f64>{
-        self.variables.get(name).copied()
+    pub fn get_variable(&self, name : &str) -> Option<Value>{
+        self.variables.get(name).cloned()
     }
 
-    pub fn set_variable(&mut self, name : String, val : f64){
+    pub fn set_variable(&mut self, name : String, val : Value){
         self.variables.insert(name, val);
     }
 
+    pub fn numeric_mode(&self) -> NumericMode{
+        self.numeric_mode
+    }
 
+    ///
+    /// Change how the literals of the following evaluations are read, values already computed are kept as they are
+    ///
+    /// `PI` and `E` are set again, to decimals of the new precision in decimal mode and
+    /// to the intervals holding them in interval mode. The constant `i` is only defined in complex mode
+    ///
+    pub fn set_numeric_mode(&mut self, mode : NumericMode){
+        let unit = Value::Complex(num_complex::Complex64::i());
+        if mode == NumericMode::Complex{
+            self.variables.insert("i".to_string(), unit);
+        }
+        else if self.variables.get("i") == Some(&unit){
+            self.variables.remove("i");
+        }
 
-    pub fn eval_expression(&self, expr : &crate::expression::ArithmeticNode) -> Result<f64, ArithmeticError>{
-        use crate::expression::ArithmeticNodeKind;
-        match &expr.kind{
-            ArithmeticNodeKind::Add(a, b) => Ok(self.eval_expression(a)? + self.eval_expression(b)?),
-            ArithmeticNodeKind::Sub(a, b) => Ok(self.eval_expression(a)? - self.eval_expression(b)?),
-            ArithmeticNodeKind::Mul(a, b) => Ok(self.eval_expression(a)? * self.eval_expression(b)?),
-            ArithmeticNodeKind::Sin(a) => Ok(self.eval_expression(a)?.sin()),
-            ArithmeticNodeKind::Cos(a) => Ok(self.eval_expression(a)?.cos()),
-            ArithmeticNodeKind::Tan(a) => Ok(self.eval_expression(a)?.tan()),
-            ArithmeticNodeKind::Deg(a) => Ok(self.eval_expression(a)?.to_radians()),
-            ArithmeticNodeKind::Div(a, b) => {
-                let num = self.eval_expression(a)?;
-                let denom = self.eval_expression(b)?;
-
-                if denom == 0.0{
-                    Err(ArithmeticError::DivideByZero(expr.span.clone()))
+        self.numeric_mode = mode;
+        let (pi, e) = match mode{
+            NumericMode::Decimal { .. } => {
+                let context = mode.decimal_context();
+                (Value::Decimal(decimal::pi(&context)), Value::Decimal(decimal::e(&context)))
+            },
+            NumericMode::Interval => (Value::Interval(Interval::pi()), Value::Interval(Interval::e())),
+            _ => (Value::Number(std::f64::consts::PI), Value::Number(std::f64::consts::E))
+        };
+        self.variables.insert("PI".to_string(), pi);
+        self.variables.insert("E".to_string(), e);
+    }
+
+    pub fn angle_mode(&self) -> AngleMode{
+        self.angle_mode
+    }
+
+    ///
+    /// Change the unit of the angles trigonometric functions take and give,
+    /// literals with a suffix such as `30°` are converted to it
+    ///
+    pub fn set_angle_mode(&mut self, mode : AngleMode){
+        self.angle_mode = mode;
+    }
+
+    ///
+    /// How results are written, see [`Evaluator::display`]
+    ///
+    pub fn format(&self) -> &Format{
+        &self.format
+    }
+
+    pub fn set_format(&mut self, format : Format){
+        self.format = format;
+    }
+
+    ///
+    /// `value` written with the format of the evaluator, which frontends use to show results
+    ///
+    pub fn display(&self, value : &Value) -> String{
+        value.format(&self.format)
+    }
+
+    ///
+    /// Tell `observer` about every statement run from now on, in place of any previous observer
+    ///
+    pub fn set_observer(&mut self, observer : impl Observer + 'static){
+        self.observer = Some(Box::new(observer));
+    }
+
+    ///
+    /// Stop telling the observer about statements, and give it back
+    ///
+    pub fn take_observer(&mut self) -> Option<Box<dyn Observer>>{
+        self.observer.take()
+    }
+
+    pub fn functions(&self) -> &FunctionRegistry{
+        &self.functions
+    }
+
+    ///
+    /// The functions callable from expressions, hosts can register their own here
+    ///
+    pub fn functions_mut(&mut self) -> &mut FunctionRegistry{
+        &mut self.functions
+    }
+
+    ///
+    /// Define or replace a function written in the expression language,
+    /// it takes precedence over a registered function of the same name
+    ///
+    pub fn define_function(&mut self, name : String, function : UserFunction){
+        self.user_functions.insert(name, function);
+    }
+
+    pub fn get_user_function(&self, name : &str) -> Option<&UserFunction>{
+        self.user_functions.get(name)
+    }
+
+    pub fn units(&self) -> &UnitRegistry{
+        &self.units
+    }
+
+    ///
+    /// The units names resolve to where a unit is expected and no variable has that name, hosts can define their own here
+    ///
+    pub fn units_mut(&mut self) -> &mut UnitRegistry{
+        &mut self.units
+    }
+
+    ///
+    /// The value of `name` on its own: a parameter, a variable, or else a unit of magnitude 1
+    ///
+    /// Units are only resolved where the scope expects one, as in `2 bar` or `to h`, so that
+    /// a misspelt variable such as `bar` alone is still reported as undefined
+    ///
+    fn lookup(&self, name : &str, scope : &Scope) -> Option<Value>{
+        scope.get(name)
+            .or_else(|| self.get_variable(name))
+            .or_else(|| self.units.get(name).filter(|_| scope.units).map(|unit| units::unit_quantity(unit, self.numeric_mode)))
+    }
+
+    fn check_arity(name : &str, arity : Arity, args : &[ArithmeticNode], span : &Span) -> Result<(), ArithmeticError>{
+        if arity.accepts(args.len()){
+            Ok(())
+        }
+        else{
+            Err(ArithmeticError::InvalidArgumentCount{
+                function: name.to_string(),
+                expected: arity,
+                got: args.len(),
+                span: span.clone()
+            })
+        }
+    }
+
+    fn eval_args(&self, args : &[ArithmeticNode], scope : &Scope) -> Result<Vec<Value>, ArithmeticError>{
+        args.iter()
+            .map(|a| self.eval_in(a, scope))
+            .collect()
+    }
+
+    fn call_function(&self, name : &str, args : &[ArithmeticNode], span : &Span, scope : &Scope) -> Result<Value, ArithmeticError>{
+        if let Some(function) = self.user_functions.get(name){
+            return self.call_user_function(name, function, args, span, scope);
+        }
+
+        let Some(function) = self.functions.get(name) else{
+            return Err(ArithmeticError::UndefinedFunction(name.to_string(), span.clone()));
+        };
+
+        Self::check_arity(name, function.arity, args, span)?;
+        let values = self.eval_args(args, scope)?;
+
+        let context = CallContext{ numeric_mode: self.numeric_mode, angle_mode: self.angle_mode };
+        (function.implementation)(&values, &context).map_err(|e| match e{
+            FunctionError::LogZero => ArithmeticError::LogZero(span.clone()),
+            FunctionError::Domain(message) => ArithmeticError::Domain{
+                function: name.to_string(),
+                message,
+                span: span.clone()
+            },
+            FunctionError::ArgumentType { index, expected, found } => ArithmeticError::Type{
+                message: format!("{name:?} expects a {expected} as argument {}, found {found}", index + 1),
+                span: args.get(index).map_or(span, |a| &a.span).clone()
+            }
+        })
+    }
+
+    fn call_user_function(&self, name : &str, function : &UserFunction, args : &[ArithmeticNode], span : &Span, scope : &Scope) -> Result<Value, ArithmeticError>{
+        Self::check_arity(name, function.arity(), args, span)?;
+
+        if scope.depth >= MAX_CALL_DEPTH{
+            return Err(ArithmeticError::RecursionLimit{ function: name.to_string(), span: span.clone() });
+        }
+
+        let args = self.eval_args(args, scope)?;
+        let inner = Scope{
+            params: &function.params,
+            args: &args,
+            depth: scope.depth + 1,
+            units: false
+        };
+
+        // The body may come from an earlier input, so the error is moved to
+        // this call, and only the outermost call is reported
+        self.eval_in(&function.body, &inner).map_err(|error| {
+            let error = match error{
+                ArithmeticError::InFunction { error, .. } => error,
+                error => Box::new(error)
+            };
+            ArithmeticError::InFunction{ function: name.to_string(), span: span.clone(), error }
+        })
+    }
+
+    pub fn eval_expression(&self, expr : &ArithmeticNode) -> Result<Value, ArithmeticError>{
+        self.eval_in(expr, &Scope::default())
+    }
+
+    ///
+    /// Evaluate the operands of `op`, then apply `apply` to them
+    ///
+    fn binary<T>(&self, op : BinOp, expr : &ArithmeticNode, a : &ArithmeticNode, b : &ArithmeticNode, scope : &Scope,
+        apply : impl FnOnce(&Value, &Value) -> Result<T, OperationError>) -> Result<T, ArithmeticError>
+    {
+        let (l, r) = (self.eval_in(a, &scope.operand(expr, false))?, self.eval_in(b, &scope.operand(expr, true))?);
+        apply(&l, &r).map_err(|e| match e{
+            OperationError::TypeMismatch => ArithmeticError::Type{
+                message: mismatch_message(op, l.type_name(), r.type_name()),
+                span: expr.operator_span().clone()
+            },
+            OperationError::IncompatibleUnits => ArithmeticError::Unit{
+                message: mismatch_message(op, &units::describe(&l), &units::describe(&r)),
+                span: expr.operator_span().clone()
+            },
+            OperationError::AbsoluteTemperatures => ArithmeticError::Unit{
+                message: format!("{}, both are absolute temperatures", mismatch_message(op, &units::describe(&l), &units::describe(&r))),
+                span: expr.operator_span().clone()
+            },
+            OperationError::UnitPower => ArithmeticError::Unit{
+                message: if op == BinOp::Exp && r.as_interval().is_some_and(|e| e.lo().fract() != 0.0){
+                    format!("Cannot raise {} to the power of {r}, its unit would have a fractional exponent", units::describe(&l))
                 }
-                else if num.is_infinite() && denom.is_infinite(){
-                    Err(ArithmeticError::InfinityDivision(expr.span.clone()))
+                else if op == BinOp::Exp{
+                    format!("Cannot raise {} to the power of {r}, its unit would have too large an exponent", units::describe(&l))
                 }
                 else{
-                    Ok(num / denom)
+                    format!("{}, the unit would have too large an exponent", mismatch_message(op, &units::describe(&l), &units::describe(&r)))
+                },
+                span: expr.operator_span().clone()
+            },
+            OperationError::IntervalDivision => ArithmeticError::Interval{
+                message: if op == BinOp::Exp{
+                    format!("Cannot raise {l} to the power of {r}, the base holds zero")
                 }
+                else{
+                    format!("Cannot divide {l} by {r}, the divisor holds zero")
+                },
+                span: expr.span.clone()
+            },
+            OperationError::IntervalPower => ArithmeticError::Interval{
+                message: format!("Cannot raise {l} to the power of {r}, the base must be positive unless the exponent is an integer"),
+                span: expr.span.clone()
             },
-            ArithmeticNodeKind::Pow(a, b) => Ok(self.eval_expression(a)?.powf(self.eval_expression(b)?)),
-            ArithmeticNodeKind::Root(a, b) => Ok( self.eval_expression(b)?.powf(1.0/self.eval_expression(a)?) ),
-            ArithmeticNodeKind::Log(a, b) => {
-                let base = self.eval_expression(a)?;
-                let value = self.eval_expression(b)?;
+            OperationError::IntervalUncertainty => ArithmeticError::Interval{
+                message: format!("Cannot give {l} an uncertainty of {r}, intervals have bounds rather than uncertainties"),
+                span: expr.span.clone()
+            },
+            OperationError::NegativeUncertainty => ArithmeticError::Domain{
+                function: op.symbol().to_string(),
+                message: format!("the uncertainty {r} is negative"),
+                span: expr.span.clone()
+            },
+            OperationError::DivideByZero => ArithmeticError::DivideByZero(expr.span.clone()),
+            OperationError::InfinityDivision => ArithmeticError::InfinityDivision(expr.span.clone())
+        })
+    }
+
+    fn arithmetic(&self, op : BinOp, expr : &ArithmeticNode, a : &ArithmeticNode, b : &ArithmeticNode, scope : &Scope) -> Result<Value, ArithmeticError>{
+        self.binary(op, expr, a, b, scope, |l, r| Value::arithmetic(op, l, r, self.numeric_mode))
+    }
+
+    ///
+    /// Evaluate `<`, `<=`, `>` or `>=`, `accept` tells whether the ordering of the operands satisfies it
+    ///
+    fn ordering(&self, op : BinOp, expr : &ArithmeticNode, a : &ArithmeticNode, b : &ArithmeticNode, scope : &Scope,
+        accept : fn(std::cmp::Ordering) -> bool) -> Result<Value, ArithmeticError>
+    {
+        let ordering = self.binary(op, expr, a, b, scope, Value::compare)?;
+        Ok(Value::Bool(ordering.is_some_and(accept)))
+    }
+
+    fn literal(&self, literal : &NumberLiteral) -> Value{
+        match self.numeric_mode{
+            NumericMode::Float | NumericMode::Complex => Value::Number(literal.value),
+            NumericMode::Integer => match Value::exact_literal(literal){
+                Some(r) if r.is_integer() => Value::Integer(r.to_integer()),
+                _ => Value::Number(literal.value)
+            },
+            NumericMode::Rational => Value::exact_literal(literal).map_or(Value::Number(literal.value), Value::Rational),
+            NumericMode::Decimal { .. } => {
+                Value::decimal_literal(literal, &self.numeric_mode.decimal_context()).map_or(Value::Number(literal.value), Value::Decimal)
+            },
+            NumericMode::Interval => {
+                Value::Interval(Value::exact_literal(literal).map_or_else(|| Interval::around(literal.value), |r| Interval::enclosing(&r)))
+            }
+        }
+    }
+
+    ///
+    /// Evaluate `[lo, hi]`, bounds that are themselves intervals contribute their outer bound
+    ///
+    fn interval(&self, expr : &ArithmeticNode, lo : &ArithmeticNode, hi : &ArithmeticNode, scope : &Scope) -> Result<Value, ArithmeticError>{
+        let bound = |node : &ArithmeticNode, which : &str| {
+            let val = self.eval_in(node, scope)?;
+            val.as_interval().ok_or_else(|| ArithmeticError::Type{
+                message: format!("Expected a number for the {which} bound of an interval, found {}", val.type_name()),
+                span: node.span.clone()
+            })
+        };
+        let (lo, hi) = (bound(lo, "lower")?.lo(), bound(hi, "upper")?.hi());
+        Interval::new(lo, hi).map(Value::Interval).ok_or_else(|| ArithmeticError::Interval{
+            message: format!("The lower bound of the interval, {lo}, is above its upper bound, {hi}"),
+            span: expr.span.clone()
+        })
+    }
+
+    ///
+    /// Evaluate `node` as a boolean, `what` names the operation needing it in the error
+    ///
+    fn boolean(&self, node : &ArithmeticNode, scope : &Scope, what : &str, span : &Span) -> Result<bool, ArithmeticError>{
+        let val = self.eval_in(node, scope)?;
+        val.as_bool().ok_or_else(|| ArithmeticError::Type{
+            message: format!("Expected a boolean for {what}, found {}", val.type_name()),
+            span: span.clone()
+        })
+    }
 
-                if value == 0.0{
-                    Err(ArithmeticError::LogZero(expr.span.clone()))
+    fn eval_in(&self, expr : &ArithmeticNode, scope : &Scope) -> Result<Value, ArithmeticError>{
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || self.eval_node(expr, scope))
+    }
+
+    fn eval_node(&self, expr : &ArithmeticNode, scope : &Scope) -> Result<Value, ArithmeticError>{
+        use crate::expression::ArithmeticNodeKind;
+        // Units stop at anything else than a product, e.g the argument of `5 f(h)` is not one
+        let plain = Scope{ units: false, ..*scope };
+        let scope = match expr.kind{
+            ArithmeticNodeKind::Mul(..) | ArithmeticNodeKind::Div(..) | ArithmeticNodeKind::Pow(..) | ArithmeticNodeKind::Variable(_) => scope,
+            _ => &plain
+        };
+        match &expr.kind{
+            ArithmeticNodeKind::Add(a, b) => self.arithmetic(BinOp::Add, expr, a, b, scope),
+            ArithmeticNodeKind::Sub(a, b) => self.arithmetic(BinOp::Sub, expr, a, b, scope),
+            ArithmeticNodeKind::Mul(a, b) => self.arithmetic(BinOp::Mul, expr, a, b, scope),
+            ArithmeticNodeKind::Div(a, b) => self.arithmetic(BinOp::Div, expr, a, b, scope),
+            ArithmeticNodeKind::IntDiv(a, b) => self.arithmetic(BinOp::IntDiv, expr, a, b, scope),
+            ArithmeticNodeKind::Mod(a, b) => self.arithmetic(BinOp::Mod, expr, a, b, scope),
+            ArithmeticNodeKind::Pow(a, b) => self.arithmetic(BinOp::Exp, expr, a, b, scope),
+            ArithmeticNodeKind::Eq(a, b) => Ok(Value::Bool(self.binary(BinOp::Eq, expr, a, b, scope, Value::equals)?)),
+            ArithmeticNodeKind::Ne(a, b) => Ok(Value::Bool(!self.binary(BinOp::Ne, expr, a, b, scope, Value::equals)?)),
+            ArithmeticNodeKind::Lt(a, b) => self.ordering(BinOp::Lt, expr, a, b, scope, std::cmp::Ordering::is_lt),
+            ArithmeticNodeKind::Le(a, b) => self.ordering(BinOp::Le, expr, a, b, scope, std::cmp::Ordering::is_le),
+            ArithmeticNodeKind::Gt(a, b) => self.ordering(BinOp::Gt, expr, a, b, scope, std::cmp::Ordering::is_gt),
+            ArithmeticNodeKind::Ge(a, b) => self.ordering(BinOp::Ge, expr, a, b, scope, std::cmp::Ordering::is_ge),
+            ArithmeticNodeKind::And(a, b) => {
+                let span = expr.operator_span();
+                Ok(Value::Bool(self.boolean(a, scope, "\"and\"", span)? && self.boolean(b, scope, "\"and\"", span)?))
+            },
+            ArithmeticNodeKind::Or(a, b) => {
+                let span = expr.operator_span();
+                Ok(Value::Bool(self.boolean(a, scope, "\"or\"", span)? || self.boolean(b, scope, "\"or\"", span)?))
+            },
+            ArithmeticNodeKind::Not(a) => Ok(Value::Bool(!self.boolean(a, scope, "\"not\"", expr.operator_span())?)),
+            ArithmeticNodeKind::Convert(a, b) => self.binary(BinOp::To, expr, a, b, scope, |l, r| units::convert(l, r, self.numeric_mode)),
+            ArithmeticNodeKind::PlusMinus(a, b) => self.arithmetic(BinOp::PlusMinus, expr, a, b, scope),
+            ArithmeticNodeKind::Interval { lo, hi } => self.interval(expr, lo, hi, scope),
+            ArithmeticNodeKind::Conditional { cond, then, otherwise } => {
+                if self.boolean(cond, scope, "the condition", &cond.span)?{
+                    self.eval_in(then, scope)
                 }
                 else{
-                    Ok(value.log(base))
+                    self.eval_in(otherwise, scope)
                 }
             },
-            ArithmeticNodeKind::Neg(a) => Ok(-self.eval_expression(a)?),
-            ArithmeticNodeKind::Literal(l) => Ok(*l),
+            ArithmeticNodeKind::Call { name, args } => self.call_function(name, args, &expr.span, scope),
+            ArithmeticNodeKind::Neg(a) => {
+                let val = self.eval_in(a, scope)?;
+                val.negate().ok_or_else(|| ArithmeticError::Type{
+                    message: format!("Cannot negate {}", val.type_name()),
+                    span: expr.operator_span().clone()
+                })
+            },
+            ArithmeticNodeKind::Literal(l) => Ok(self.literal(l)),
+            ArithmeticNodeKind::Angle(l, unit) => {
+                Ok(angle::convert(&self.literal(l), *unit, self.angle_mode, self.numeric_mode).expect("literals are numbers"))
+            },
+            ArithmeticNodeKind::Boolean(b) => Ok(Value::Bool(*b)),
             ArithmeticNodeKind::Variable(name) => {
-                let v = self.get_variable(name);
-                match v{
-                    Some(v) => Ok(v),
-                    None => Err(ArithmeticError::UndefinedVariable(name.clone(), expr.span.clone()))
-                }
+                self.lookup(name, scope).ok_or_else(|| ArithmeticError::UndefinedVariable(name.clone(), expr.span.clone()))
             }
         }
     }
 
-    pub fn eval_statement(&mut self, stmnt : &crate::statement::Statement) -> Result<(), ArithmeticError>{
-        use crate::statement::Statement;
+    ///
+    /// Evaluate a statement, applying any assignment or definition it performs
+    ///
+    /// Returns the value of the expression, which for an assignment is the assigned value.
+    /// A function definition has no value
+    ///
+    pub fn exec_statement(&mut self, stmnt : &Statement) -> Result<Option<Value>, ArithmeticError>{
+        self.eval_statement(stmnt).map(StatementOutcome::into_value)
+    }
+
+    ///
+    /// Evaluate a statement, applying any assignment or definition it performs, and tell the observer about it
+    ///
+    /// Nothing is written out, frontends show the outcome as they see fit
+    ///
+    pub fn eval_statement(&mut self, stmnt : &Statement) -> Result<StatementOutcome, ArithmeticError>{
+        let outcome = self.apply_statement(stmnt);
+        if let Some(observer) = &mut self.observer{
+            match &outcome{
+                Ok(outcome) => observer.statement(stmnt, outcome),
+                Err(error) => observer.error(stmnt, error)
+            }
+        }
+        outcome
+    }
+
+    fn apply_statement(&mut self, stmnt : &Statement) -> Result<StatementOutcome, ArithmeticError>{
         match stmnt{
-            Statement::Assignment { var_name, expr } => {
+            Statement::Assignment { var_name, expr, .. } => {
                 let val = self.eval_expression(expr)?;
-                println!("EVAL: Setting '{var_name}' = {val}");
-                self.set_variable(var_name.to_string(), val);
+                self.set_variable(var_name.to_string(), val.clone());
+                Ok(StatementOutcome::Assigned{ name: var_name.to_string(), value: val })
             },
-            Statement::Expr(e) => {
-                let res = self.eval_expression(e)?;
-                println!("EVAL: Expression resolves to {res}");
-            }
-        };
+            Statement::FunctionDef { name, params, body, .. } => {
+                self.define_function(name.to_string(), UserFunction{ params: params.clone(), body: body.clone() });
+                Ok(StatementOutcome::FunctionDefined{ name: name.to_string(), params: params.clone() })
+            },
+            Statement::UnitDef { name, expr, .. } => {
+                let val = self.eval_expression(expr)?;
+                let unit = units::definition(name, &val).map_err(|message| ArithmeticError::Unit{
+                    message: format!("Invalid unit {name:?}: {message}"),
+                    span: expr.span.clone()
+                })?;
+                self.units.define(unit).expect("unit names are identifiers");
+                Ok(StatementOutcome::UnitDefined{ name: name.to_string() })
+            },
+            Statement::Expr(e) => self.eval_expression(e).map(StatementOutcome::Value)
+        }
+    }
+
+    ///
+    /// Run every statement of the program in order
+    ///
+    /// Returns the value of each statement that has one, or stops at the first one that fails
+    ///
+    pub fn eval_program(&mut self, program : &crate::program::Program) -> Result<Vec<Value>, ProgramError>{
+        let outcomes = self.run_program(program)?;
+        Ok(outcomes.into_iter().filter_map(StatementOutcome::into_value).collect())
+    }
 
-        Ok(())
+    ///
+    /// Run every statement of the program in order
+    ///
+    /// Returns the outcome of each statement, or stops at the first one that fails
+    ///
+    pub fn run_program(&mut self, program : &crate::program::Program) -> Result<Vec<StatementOutcome>, ProgramError>{
+        program.statements.iter()
+            .enumerate()
+            .map(|(index, stmnt)| self.eval_statement(stmnt).map_err(|error| ProgramError{
+                index,
+                statement: stmnt.span(),
+                error: Box::new(error)
+            }))
+            .collect()
     }
 }
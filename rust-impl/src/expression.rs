@@ -1,46 +1,117 @@
-use crate::lexer::{Lexeme, LexemeKind, Span};
+use crate::angle::AngleMode;
+use crate::lexer::{Lexeme, LexemeKind, NumberLiteral, Span};
 use crate::tokof;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BinOp {
     Add,
     Sub,
     Mul,
     Div,
+    /// Division rounded down to an integer, `//`
+    IntDiv,
+    /// Remainder of `IntDiv`, with the sign of the divisor, `%`
+    Mod,
     Exp,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    /// Conversion to the unit of the right operand, `to`
+    To,
+    /// Uncertainty of the left operand, `+-` or `±`
+    PlusMinus,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ArithmeticNodeKind {
     Add(NodeRef, NodeRef),
     Sub(NodeRef, NodeRef),
     Mul(NodeRef, NodeRef),
     Div(NodeRef, NodeRef),
+    IntDiv(NodeRef, NodeRef),
+    Mod(NodeRef, NodeRef),
     Pow(NodeRef, NodeRef),
-    Root(NodeRef, NodeRef),
-    // Log(base, value)
-    Log(NodeRef, NodeRef),
-    Sin(NodeRef),
-    Cos(NodeRef),
-    Tan(NodeRef),
-    /// Convert degrees to rads
-    Deg(NodeRef),
+    Eq(NodeRef, NodeRef),
+    Ne(NodeRef, NodeRef),
+    Lt(NodeRef, NodeRef),
+    Le(NodeRef, NodeRef),
+    Gt(NodeRef, NodeRef),
+    Ge(NodeRef, NodeRef),
+    /// Only evaluates the right side when the left one is true
+    And(NodeRef, NodeRef),
+    /// Only evaluates the right side when the left one is false
+    Or(NodeRef, NodeRef),
+    Not(NodeRef),
+    /// `value to unit`, expresses a quantity in another unit
+    Convert(NodeRef, NodeRef),
+    /// `value +- sigma`, a measurement with a standard uncertainty
+    PlusMinus(NodeRef, NodeRef),
+    /// `[lo, hi]`, the numbers between the two bounds
+    Interval {
+        lo: NodeRef,
+        hi: NodeRef,
+    },
+    /// `if(cond, then, otherwise)` or `cond ? then : otherwise`, only the taken branch is evaluated
+    Conditional {
+        cond: NodeRef,
+        then: NodeRef,
+        otherwise: NodeRef,
+    },
+    /// Invocation of a function, resolved by name during evaluation
+    Call {
+        name: String,
+        args: Vec<ArithmeticNode>,
+    },
     Neg(NodeRef),
-    Literal(f64),
+    Literal(NumberLiteral),
+    /// A literal with the unit of its angle, e.g `30°`
+    Angle(NumberLiteral, AngleMode),
+    Boolean(bool),
     Variable(String),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ArithmeticNode {
     pub span: Span,
     pub kind: ArithmeticNodeKind,
+    /// Span of the operator of an operator node, e.g the `+` of `a + b`.
+    /// For an implicit multiplication, the gap between the operands
+    pub op_span: Option<Span>,
+    /// Whether this is an implicit multiplication, e.g `5 m`
+    pub juxtaposed: bool,
 }
 
 type NodeRef = Box<ArithmeticNode>;
 
 impl ArithmeticNode {
     pub fn new(kind: ArithmeticNodeKind, span: Span) -> Self {
-        Self { kind, span }
+        Self {
+            kind,
+            span,
+            op_span: None,
+            juxtaposed: false,
+        }
+    }
+
+    pub fn with_operator(kind: ArithmeticNodeKind, span: Span, op_span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            op_span: Some(op_span),
+            juxtaposed: false,
+        }
+    }
+
+    ///
+    /// Span of the operator, or of the whole node if it has none
+    ///
+    pub fn operator_span(&self) -> &Span {
+        self.op_span.as_ref().unwrap_or(&self.span)
     }
 
     ///
@@ -52,368 +123,649 @@ impl ArithmeticNode {
             ArithmeticNodeKind::Sub(a, b) => format!("({} {} -)", a.into_rpn(), b.into_rpn()),
             ArithmeticNodeKind::Mul(a, b) => format!("({} {} *)", a.into_rpn(), b.into_rpn()),
             ArithmeticNodeKind::Div(a, b) => format!("({} {} /)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::IntDiv(a, b) => format!("({} {} //)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::Mod(a, b) => format!("({} {} %)", a.into_rpn(), b.into_rpn()),
             ArithmeticNodeKind::Pow(a, b) => format!("({} {} ^)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::Eq(a, b) => format!("({} {} ==)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::Ne(a, b) => format!("({} {} !=)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::Lt(a, b) => format!("({} {} <)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::Le(a, b) => format!("({} {} <=)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::Gt(a, b) => format!("({} {} >)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::Ge(a, b) => format!("({} {} >=)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::And(a, b) => format!("({} {} and)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::Or(a, b) => format!("({} {} or)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::Not(a) => format!("({} not)", a.into_rpn()),
+            ArithmeticNodeKind::Convert(a, b) => format!("({} {} to)", a.into_rpn(), b.into_rpn()),
+            ArithmeticNodeKind::PlusMinus(a, b) => {
+                format!("({} {} +-)", a.into_rpn(), b.into_rpn())
+            }
+            ArithmeticNodeKind::Interval { lo, hi } => {
+                format!("({} {} [])", lo.into_rpn(), hi.into_rpn())
+            }
+            ArithmeticNodeKind::Conditional {
+                cond,
+                then,
+                otherwise,
+            } => format!(
+                "({} {} {} ?:)",
+                cond.into_rpn(),
+                then.into_rpn(),
+                otherwise.into_rpn()
+            ),
             ArithmeticNodeKind::Neg(a) => format!("({} -)", a.into_rpn()),
-            ArithmeticNodeKind::Sin(a) => format!("({} sin)", a.into_rpn()),
-            ArithmeticNodeKind::Cos(a) => format!("({} cos)", a.into_rpn()),
-            ArithmeticNodeKind::Tan(a) => format!("({} tan)", a.into_rpn()),
-            ArithmeticNodeKind::Deg(a) => format!("({} deg)", a.into_rpn()),
-            ArithmeticNodeKind::Root(a, b) => format!("({} {} nrt)", a.into_rpn(), b.into_rpn()),
-            ArithmeticNodeKind::Log(a, b) => format!("({} {} log)", a.into_rpn(), b.into_rpn()),
-            ArithmeticNodeKind::Literal(l) => format!("{}", l),
+            ArithmeticNodeKind::Call { name, args } => {
+                let args = args.iter().map(|a| a.into_rpn() + " ").collect::<String>();
+                format!("({args}{name})")
+            }
+            ArithmeticNodeKind::Literal(l) => format!("{}", l.value),
+            ArithmeticNodeKind::Angle(l, unit) => format!("{}{}", l.value, unit.suffix()),
+            ArithmeticNodeKind::Boolean(b) => format!("{}", b),
             ArithmeticNodeKind::Variable(v) => v.to_string(),
         }
     }
 }
 
+impl BinOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::IntDiv => "//",
+            BinOp::Mod => "%",
+            BinOp::Exp => "^",
+            BinOp::Eq => "==",
+            BinOp::Ne => "!=",
+            BinOp::Lt => "<",
+            BinOp::Le => "<=",
+            BinOp::Gt => ">",
+            BinOp::Ge => ">=",
+            BinOp::And => "and",
+            BinOp::Or => "or",
+            BinOp::To => "to",
+            BinOp::PlusMinus => "+-",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ParserError {
-    InvalidUnaryOperator(BinOp),
-    InvalidArgumentCount { expected: usize, got: usize },
+    InvalidUnaryOperator(BinOp, Span),
     UnexpectedToken(Lexeme),
-    UnrecognizedFunction(String, Span),
+    /// An expression was expected, but there was nothing
+    EmptyExpression(Span),
+    /// Points at the opening parenthesis that is never closed
+    UnclosedParen(Span),
+    /// Points at the opening bracket of an interval that is never closed
+    UnclosedBracket(Span),
+    /// The expression nests deeper than the parser supports
+    NestingTooDeep(Span),
+    /// `if` not followed by exactly three arguments, points at the `if`
+    ConditionalArguments(Span),
+    /// Points at a `?` without its `:`
+    MissingColon(Span),
 }
 
-impl ArithmeticNode {
+impl ParserError {
+    pub fn span(&self) -> &Span {
+        match self {
+            Self::InvalidUnaryOperator(_, span)
+            | Self::EmptyExpression(span)
+            | Self::UnclosedParen(span)
+            | Self::UnclosedBracket(span)
+            | Self::NestingTooDeep(span)
+            | Self::ConditionalArguments(span)
+            | Self::MissingColon(span) => span,
+            Self::UnexpectedToken(lex) => &lex.span,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidUnaryOperator(..) => "E201",
+            Self::UnexpectedToken(_) => "E203",
+            Self::EmptyExpression(_) => "E205",
+            Self::UnclosedParen(_) => "E206",
+            Self::NestingTooDeep(_) => "E207",
+            Self::ConditionalArguments(_) => "E209",
+            Self::MissingColon(_) => "E210",
+            Self::UnclosedBracket(_) => "E211",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::InvalidUnaryOperator(op, _) => {
+                format!("{:?} cannot be used as a unary operator", op.symbol())
+            }
+            Self::UnexpectedToken(lex) => format!("Unexpected token: {:?}", lex.kind.to_string()),
+            Self::EmptyExpression(_) => "Expected an expression".into(),
+            Self::UnclosedParen(_) => "Unclosed parenthesis".into(),
+            Self::UnclosedBracket(_) => "Unclosed bracket".into(),
+            Self::NestingTooDeep(_) => "Expression is nested too deeply".into(),
+            Self::ConditionalArguments(_) => {
+                "Expected if(condition, value if true, value if false)".into()
+            }
+            Self::MissingColon(_) => "Expected ':' after the value if true".into(),
+        }
+    }
+
+    pub fn contextualize(&self, source: &str) -> String {
+        self.span().underline(source, Some(&self.message()))
+    }
+}
+
+impl std::fmt::Display for ParserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ParserError {}
+
+impl BinOp {
     ///
-    /// Split the given expression at the separator
+    /// The binding power of the operator when used in infix position
     ///
-    /// takes indentation into account
+    /// Returns the (left, right) binding powers, a right power lower than the
+    /// left one makes the operator right associative
     ///
-    fn split_at<'a>(expr: &'a [Lexeme], sep: &LexemeKind) -> Vec<&'a [Lexeme]> {
-        let mut ind = 0;
-        let mut ret = Vec::new();
-
-        let mut item_start = 0;
+    fn binding_power(&self) -> (u8, u8) {
+        match self {
+            BinOp::Or => (3, 4),
+            BinOp::And => (5, 6),
+            BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => (9, 10),
+            // Looser than arithmetic but tighter than comparisons, `a to b == c` compares the converted `a`
+            BinOp::To => (10, 11),
+            BinOp::Add | BinOp::Sub => (11, 12),
+            // Tighter than `+` so that `a + b +- s` is `a + (b +- s)`, looser than `*` so that `a +- 2s` is `a +- (2s)`
+            BinOp::PlusMinus => (12, 13),
+            BinOp::Mul | BinOp::Div | BinOp::IntDiv | BinOp::Mod => (13, 14),
+            BinOp::Exp => (19, 18),
+        }
+    }
 
-        for (i, tok) in expr.iter().enumerate() {
-            match &tok.kind {
-                LexemeKind::OpenParen => ind += 1,
-                LexemeKind::CloseParen => ind -= 1,
-                l if l == sep && ind == 0 => {
-                    // We encounter the separator at the root level
-                    let slice = &expr[item_start..i];
-                    ret.push(slice);
-                    item_start = i + 1;
-                }
-                // Just another content token, do nothing
-                _ => (),
-            }
+    fn from_lexeme(kind: &LexemeKind) -> Option<BinOp> {
+        match kind {
+            LexemeKind::Plus => Some(BinOp::Add),
+            LexemeKind::Minus => Some(BinOp::Sub),
+            LexemeKind::Star => Some(BinOp::Mul),
+            LexemeKind::Slash => Some(BinOp::Div),
+            LexemeKind::DoubleSlash => Some(BinOp::IntDiv),
+            LexemeKind::Percent => Some(BinOp::Mod),
+            LexemeKind::Karat => Some(BinOp::Exp),
+            LexemeKind::DoubleEquals => Some(BinOp::Eq),
+            LexemeKind::NotEquals => Some(BinOp::Ne),
+            LexemeKind::Less => Some(BinOp::Lt),
+            LexemeKind::LessEquals => Some(BinOp::Le),
+            LexemeKind::Greater => Some(BinOp::Gt),
+            LexemeKind::GreaterEquals => Some(BinOp::Ge),
+            LexemeKind::And => Some(BinOp::And),
+            LexemeKind::Or => Some(BinOp::Or),
+            LexemeKind::To => Some(BinOp::To),
+            LexemeKind::PlusMinus => Some(BinOp::PlusMinus),
+            _ => None,
         }
+    }
+}
+
+/// Binding powers of implicit multiplication, tighter than `*` and `/` so that
+/// `1/2x` is `1/(2x)`, but looser than `^` so that `2x^2` is `2(x^2)`
+const IMPLICIT_MUL_BINDING_POWER: (u8, u8) = (15, 16);
+
+/// Binding power of the prefix `-` and `+`, binds tighter than `*` but looser than `^`,
+/// so that `-2^2` is `-(2^2)`
+const PREFIX_BINDING_POWER: u8 = 17;
+
+/// Binding power of the prefix `not`, looser than the comparisons so that
+/// `not a == b` is `not (a == b)`, but tighter than `and` and `or`
+const NOT_BINDING_POWER: u8 = 7;
+
+/// Binding powers of `cond ? then : otherwise`, the loosest operator.
+/// It is right associative, so `a ? b : c ? d : e` is `a ? b : (c ? d : e)`
+const CONDITIONAL_BINDING_POWER: (u8, u8) = (2, 1);
+
+/// Deepest nesting of sub-expressions accepted before giving up, this keeps
+/// hostile inputs such as ten thousand `(` from overflowing the stack.
+/// Each operator of a chain nests the expression before it, so `1 + 1 + 1` is
+/// as deep as `(1 + 1) + 1`, which bounds the recursion of dropping the tree
+const MAX_DEPTH: usize = 1024;
+
+/// Stack left below which parsing a sub-expression allocates a new stack segment,
+/// frames are large in debug builds and threads may have small stacks
+const STACK_RED_ZONE: usize = 64 * 1024;
+const STACK_SEGMENT_SIZE: usize = 1024 * 1024;
+
+///
+/// Precedence climbing parser over a token slice
+///
+struct Parser<'a> {
+    toks: &'a [Lexeme],
+    pos: usize,
+    /// Span of the last consumed token, used to locate missing expressions
+    prev: Span,
+    depth: usize,
+    options: &'a ParseOptions,
+}
 
-        // Account for final element
+///
+/// Settings that change which expressions the parser accepts
+///
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Read a value directly followed by an identifier or a parenthesized
+    /// expression as a multiplication, e.g `2x`, `3(a + b)` or `(a + b)(a - b)`.
+    ///
+    /// A number on the right is never implied, so `2 3` or `x 2` stay errors,
+    /// and an identifier directly followed by `(` is always a function call
+    pub implicit_multiplication: bool,
+}
 
-        if item_start < expr.len() {
-            ret.push(&expr[item_start..]);
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            implicit_multiplication: true,
         }
+    }
+}
 
-        ret
+impl ParseOptions {
+    ///
+    /// Only accept explicit operators
+    ///
+    pub fn strict() -> Self {
+        Self {
+            implicit_multiplication: false,
+        }
     }
+}
 
-    pub fn parse(expr: &[Lexeme]) -> Result<ArithmeticNode, ParserError> {
-        let span = expr[0].span.up_to(&expr[expr.len() - 1].span);
-        // Handle a one-element expr
-        if let [lex] = expr {
-            return match &lex.kind {
-                LexemeKind::Number(val) => {
-                    Ok(ArithmeticNode::new(ArithmeticNodeKind::Literal(*val), span))
-                }
-                LexemeKind::Word(val) => Ok(ArithmeticNode::new(
-                    ArithmeticNodeKind::Variable(val.clone()),
-                    span,
-                )),
-                _ => Err(ParserError::UnexpectedToken(lex.clone())),
-            };
+impl<'a> Parser<'a> {
+    fn new(toks: &'a [Lexeme], prev: &Span, options: &'a ParseOptions) -> Self {
+        Self {
+            toks,
+            pos: 0,
+            prev: prev.clone(),
+            depth: 0,
+            options,
         }
+    }
+
+    fn peek(&self) -> Option<&'a Lexeme> {
+        self.toks.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<&'a Lexeme> {
+        let tok = self.toks.get(self.pos)?;
+        self.pos += 1;
+        self.prev = tok.span.clone();
+        Some(tok)
+    }
+
+    ///
+    /// Parse an expression made of operators binding at least as tight as `min_bp`
+    ///
+    fn expression(&mut self, min_bp: u8) -> Result<ArithmeticNode, ParserError> {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+            self.expression_inner(min_bp)
+        })
+    }
 
-        // We use the following algorithm in order to parse the expression
-        //
-        // 1. Find "split points"
-        // 2. Find the final "split point" with the lowest priority, (construciton happens in
-        //    reverse)
-        // 3. Subparse both sides of the split point
-
-        #[derive(Debug)]
-        struct SplitPoint {
-            idx: usize,
-            op: BinOp,
+    fn expression_inner(&mut self, min_bp: u8) -> Result<ArithmeticNode, ParserError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            let span = self
+                .peek()
+                .map_or_else(|| self.prev.after(), |t| t.span.clone());
+            return Err(ParserError::NestingTooDeep(span));
         }
 
-        impl SplitPoint {
-            fn new(idx: usize, op: BinOp) -> Self {
-                Self { op, idx }
-            }
+        let mut lhs = self.prefix()?;
+        let mut chained = 0;
 
-            fn priority(&self) -> u8 {
-                match self.op {
-                    BinOp::Exp => 2,
-                    BinOp::Mul => 1,
-                    BinOp::Div => 1,
-                    BinOp::Add => 0,
-                    BinOp::Sub => 0,
+        while let Some(tok) = self.peek() {
+            if let tokof!(Question) = tok {
+                let (l_bp, r_bp) = CONDITIONAL_BINDING_POWER;
+                if l_bp < min_bp {
+                    break;
                 }
+                chained += 1;
+                self.nest(&tok.span)?;
+                self.advance();
+                lhs = self.conditional(lhs, tok, r_bp)?;
+                continue;
             }
-        }
 
-        let mut split_points = Vec::<SplitPoint>::new();
-
-        let mut ind: usize = 0;
-
-        // if the previous encountered token is an operator
-        // used for the prefix op hack
-        let mut prev_enc_op = false;
-
-        for (idx, l) in expr.iter().enumerate() {
-            if ind == 0 {
-                match &l.kind {
-                    LexemeKind::Plus => {
-                        if !prev_enc_op {
-                            split_points.push(SplitPoint::new(idx, BinOp::Add));
-                        }
-                        prev_enc_op = true;
-                    }
-                    LexemeKind::Minus => {
-                        if !prev_enc_op {
-                            split_points.push(SplitPoint::new(idx, BinOp::Sub));
-                        }
-                        prev_enc_op = true;
-                    }
-                    LexemeKind::Star => {
-                        if !prev_enc_op {
-                            split_points.push(SplitPoint::new(idx, BinOp::Mul))
-                        }
-                        prev_enc_op = true;
-                    }
-                    LexemeKind::Slash => {
-                        if !prev_enc_op {
-                            split_points.push(SplitPoint::new(idx, BinOp::Div))
-                        }
-                        prev_enc_op = true;
-                    }
-                    LexemeKind::Karat => {
-                        if !prev_enc_op {
-                            split_points.push(SplitPoint::new(idx, BinOp::Exp))
-                        }
-                        prev_enc_op = true;
-                    }
-                    LexemeKind::OpenParen => {
-                        ind += 1;
-                        prev_enc_op = false;
-                    }
-                    LexemeKind::CloseParen => return Err(ParserError::UnexpectedToken(l.clone())),
-                    _ => {
-                        prev_enc_op = false;
-                    }
+            let (op, implicit) = match BinOp::from_lexeme(&tok.kind) {
+                Some(op) => (op, false),
+                // Juxtaposed values, the token is the start of the right operand
+                None if self.options.implicit_multiplication
+                    && matches!(
+                        tok.kind,
+                        LexemeKind::Word(_) | LexemeKind::OpenParen | LexemeKind::OpenBracket
+                    ) =>
+                {
+                    (BinOp::Mul, true)
                 }
+                None => break,
+            };
+
+            let (l_bp, r_bp) = if implicit {
+                IMPLICIT_MUL_BINDING_POWER
             } else {
-                match &l.kind {
-                    LexemeKind::OpenParen => {
-                        ind += 1;
-                    }
-                    LexemeKind::CloseParen => {
-                        ind -= 1;
-                    }
-                    _ => (),
-                }
+                op.binding_power()
+            };
+            if l_bp < min_bp {
+                break;
+            }
+            chained += 1;
+            self.nest(&tok.span)?;
+            if !implicit {
+                self.advance();
             }
+
+            let rhs = self.expression(r_bp)?;
+            let span = lhs.span.up_to(&rhs.span);
+            let op_span = if implicit {
+                Span::new(lhs.span.end, rhs.span.begin)
+            } else {
+                tok.span.clone()
+            };
+            let (lhs_ref, rhs_ref) = (Box::new(lhs), Box::new(rhs));
+
+            let kind = match op {
+                BinOp::Add => ArithmeticNodeKind::Add(lhs_ref, rhs_ref),
+                BinOp::Sub => ArithmeticNodeKind::Sub(lhs_ref, rhs_ref),
+                BinOp::Mul => ArithmeticNodeKind::Mul(lhs_ref, rhs_ref),
+                BinOp::Div => ArithmeticNodeKind::Div(lhs_ref, rhs_ref),
+                BinOp::IntDiv => ArithmeticNodeKind::IntDiv(lhs_ref, rhs_ref),
+                BinOp::Mod => ArithmeticNodeKind::Mod(lhs_ref, rhs_ref),
+                BinOp::Exp => ArithmeticNodeKind::Pow(lhs_ref, rhs_ref),
+                BinOp::Eq => ArithmeticNodeKind::Eq(lhs_ref, rhs_ref),
+                BinOp::Ne => ArithmeticNodeKind::Ne(lhs_ref, rhs_ref),
+                BinOp::Lt => ArithmeticNodeKind::Lt(lhs_ref, rhs_ref),
+                BinOp::Le => ArithmeticNodeKind::Le(lhs_ref, rhs_ref),
+                BinOp::Gt => ArithmeticNodeKind::Gt(lhs_ref, rhs_ref),
+                BinOp::Ge => ArithmeticNodeKind::Ge(lhs_ref, rhs_ref),
+                BinOp::And => ArithmeticNodeKind::And(lhs_ref, rhs_ref),
+                BinOp::Or => ArithmeticNodeKind::Or(lhs_ref, rhs_ref),
+                BinOp::To => ArithmeticNodeKind::Convert(lhs_ref, rhs_ref),
+                BinOp::PlusMinus => ArithmeticNodeKind::PlusMinus(lhs_ref, rhs_ref),
+            };
+            lhs = ArithmeticNode::with_operator(kind, span, op_span);
+            lhs.juxtaposed = implicit;
         }
 
-        let target_split = split_points.iter().enumerate().max_by(|x, y| {
-            let px = x.1.priority();
-            let py = y.1.priority();
+        self.depth -= 1 + chained;
+        Ok(lhs)
+    }
 
-            let cmp = px.cmp(&py);
+    ///
+    /// Go one level deeper for the operator at `op_span`, which applies to the expression before it
+    ///
+    fn nest(&mut self, op_span: &Span) -> Result<(), ParserError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(ParserError::NestingTooDeep(op_span.clone()));
+        }
+        Ok(())
+    }
 
-            match cmp {
-                std::cmp::Ordering::Equal => x.0.cmp(&y.0),
-                std::cmp::Ordering::Greater => std::cmp::Ordering::Less,
-                std::cmp::Ordering::Less => std::cmp::Ordering::Greater,
-            }
-        });
-
-        match target_split {
-            Some((_, split)) => {
-                if split.idx == 0 {
-                    // We are applying a unary operation to the value on the left
-
-                    match split.op {
-                        BinOp::Exp => Err(ParserError::InvalidUnaryOperator(BinOp::Exp)),
-                        BinOp::Mul => Err(ParserError::InvalidUnaryOperator(BinOp::Mul)),
-                        BinOp::Div => Err(ParserError::InvalidUnaryOperator(BinOp::Div)),
-                        BinOp::Add => ArithmeticNode::parse(&expr[1..]),
-                        BinOp::Sub => Ok(ArithmeticNode::new(
-                            ArithmeticNodeKind::Neg(Box::new(ArithmeticNode::parse(&expr[1..])?)),
-                            span,
-                        )),
-                    }
+    ///
+    /// Parse a value, possibly preceded by prefix operators
+    ///
+    fn prefix(&mut self) -> Result<ArithmeticNode, ParserError> {
+        // Nothing left where a value is expected
+        let Some(tok) = self.peek().filter(|t| {
+            !matches!(
+                t.kind,
+                LexemeKind::Comma | LexemeKind::CloseParen | LexemeKind::CloseBracket
+            )
+        }) else {
+            return Err(ParserError::EmptyExpression(self.prev.after()));
+        };
+        self.advance();
+
+        match &tok.kind {
+            LexemeKind::Number(val) => match self.peek() {
+                Some(suffix @ tokof!(Angle(unit))) => {
+                    self.advance();
+                    Ok(ArithmeticNode::new(
+                        ArithmeticNodeKind::Angle(val.clone(), *unit),
+                        tok.span.up_to(&suffix.span),
+                    ))
+                }
+                _ => Ok(ArithmeticNode::new(
+                    ArithmeticNodeKind::Literal(val.clone()),
+                    tok.span.clone(),
+                )),
+            },
+            LexemeKind::True | LexemeKind::False => Ok(ArithmeticNode::new(
+                ArithmeticNodeKind::Boolean(tok.kind == LexemeKind::True),
+                tok.span.clone(),
+            )),
+            LexemeKind::Word(name) => {
+                if let Some(open @ tokof!(OpenParen)) = self.peek() {
+                    self.advance();
+                    self.call(tok, open)
                 } else {
-                    let (lhs, mut rhs) = expr.split_at(split.idx);
-                    rhs = &rhs[1..];
-
-                    let left_subtree = ArithmeticNode::parse(lhs)?;
-                    let right_subtree = ArithmeticNode::parse(rhs)?;
-
-                    match split.op {
-                        BinOp::Mul => Ok(ArithmeticNode::new(
-                            ArithmeticNodeKind::Mul(
-                                Box::new(left_subtree),
-                                Box::new(right_subtree),
-                            ),
-                            span,
-                        )),
-                        BinOp::Div => Ok(ArithmeticNode::new(
-                            ArithmeticNodeKind::Div(
-                                Box::new(left_subtree),
-                                Box::new(right_subtree),
-                            ),
-                            span,
-                        )),
-                        BinOp::Sub => Ok(ArithmeticNode::new(
-                            ArithmeticNodeKind::Sub(
-                                Box::new(left_subtree),
-                                Box::new(right_subtree),
-                            ),
-                            span,
-                        )),
-                        BinOp::Add => Ok(ArithmeticNode::new(
-                            ArithmeticNodeKind::Add(
-                                Box::new(left_subtree),
-                                Box::new(right_subtree),
-                            ),
-                            span,
-                        )),
-                        BinOp::Exp => Ok(ArithmeticNode::new(
-                            ArithmeticNodeKind::Pow(
-                                Box::new(left_subtree),
-                                Box::new(right_subtree),
-                            ),
-                            span,
-                        )),
-                    }
+                    Ok(ArithmeticNode::new(
+                        ArithmeticNodeKind::Variable(name.clone()),
+                        tok.span.clone(),
+                    ))
                 }
             }
-            None => {
-                // There is no split point, this occurs in the following cases:
-                // - Our expression is fully parenthesized
-                // - Our expression is the invocation of a function (i.e sqrt)
-
-                match expr {
-                    // Fully parenthesized expression
-                    [tokof!(OpenParen), .., tokof!(CloseParen)] => {
-                        let inner_expr = &expr[1..expr.len() - 1];
-
-                        ArithmeticNode::parse(inner_expr)
-                    }
-
-                    // Function invocation
-                    [tokof!(Word(fn_name)), tokof!(OpenParen), .., tokof!(CloseParen)] => {
-                        let raw_args = &expr[2..expr.len() - 1];
-
-                        let args = ArithmeticNode::split_at(raw_args, &LexemeKind::Comma);
-                        let mut args = args
-                            .into_iter()
-                            .map(ArithmeticNode::parse)
-                            .collect::<Result<Vec<_>, _>>()?;
-
-                        match fn_name.as_str() {
-                            // Nth root, arg 1 is the root number and arg 2 is the target
-                            "nrt" => {
-                                if args.len() != 2 {
-                                    return Err(ParserError::InvalidArgumentCount {
-                                        expected: 2,
-                                        got: args.len(),
-                                    });
-                                }
-
-                                let (nth_root, target) = (args.remove(0), args.remove(0));
-
-                                Ok(ArithmeticNode::new(
-                                    ArithmeticNodeKind::Root(Box::new(nth_root), Box::new(target)),
-                                    span,
-                                ))
-                            }
-                            "log" => {
-                                if args.len() != 2 {
-                                    return Err(ParserError::InvalidArgumentCount {
-                                        expected: 2,
-                                        got: args.len(),
-                                    });
-                                }
-
-                                let (base, target) = (args.remove(0), args.remove(0));
-
-                                Ok(ArithmeticNode::new(
-                                    ArithmeticNodeKind::Log(Box::new(base), Box::new(target)),
-                                    span,
-                                ))
-                            }
-                            "sin" => {
-                                if args.len() != 1 {
-                                    return Err(ParserError::InvalidArgumentCount {
-                                        expected: 1,
-                                        got: args.len(),
-                                    });
-                                }
-
-                                let val = args.remove(0);
-                                Ok(ArithmeticNode::new(
-                                    ArithmeticNodeKind::Sin(Box::new(val)),
-                                    span,
-                                ))
-                            }
-                            "cos" => {
-                                if args.len() != 1 {
-                                    return Err(ParserError::InvalidArgumentCount {
-                                        expected: 1,
-                                        got: args.len(),
-                                    });
-                                }
-
-                                let val = args.remove(0);
-                                Ok(ArithmeticNode::new(
-                                    ArithmeticNodeKind::Cos(Box::new(val)),
-                                    span,
-                                ))
-                            }
-                            "tan" => {
-                                if args.len() != 1 {
-                                    return Err(ParserError::InvalidArgumentCount {
-                                        expected: 1,
-                                        got: args.len(),
-                                    });
-                                }
-
-                                let val = args.remove(0);
-                                Ok(ArithmeticNode::new(
-                                    ArithmeticNodeKind::Tan(Box::new(val)),
-                                    span,
-                                ))
-                            }
-                            "deg" => {
-                                if args.len() != 1 {
-                                    return Err(ParserError::InvalidArgumentCount {
-                                        expected: 1,
-                                        got: args.len(),
-                                    });
-                                }
-
-                                let val = args.remove(0);
-                                Ok(ArithmeticNode::new(
-                                    ArithmeticNodeKind::Deg(Box::new(val)),
-                                    span,
-                                ))
-                            }
-                            _ => panic!("Unrecognized function: {fn_name:?}"),
-                        }
-                    }
-
-                    // Literal
-                    [tokof!(Number(lit))] => {
-                        Ok(ArithmeticNode::new(ArithmeticNodeKind::Literal(*lit), span))
-                    }
-                    _ => panic!("Unrecognized Expression: {expr:?}"),
+            LexemeKind::OpenParen => {
+                let mut inner = self.expression(0)?;
+                let close = self.close_paren(tok)?;
+
+                // The parenthesis are part of the expression
+                inner.span = tok.span.up_to(&close.span);
+                Ok(inner)
+            }
+            LexemeKind::Minus => {
+                let operand = self.expression(PREFIX_BINDING_POWER)?;
+                let span = tok.span.up_to(&operand.span);
+                Ok(ArithmeticNode::with_operator(
+                    ArithmeticNodeKind::Neg(Box::new(operand)),
+                    span,
+                    tok.span.clone(),
+                ))
+            }
+            LexemeKind::Plus => self.expression(PREFIX_BINDING_POWER),
+            LexemeKind::Not => {
+                let operand = self.expression(NOT_BINDING_POWER)?;
+                let span = tok.span.up_to(&operand.span);
+                Ok(ArithmeticNode::with_operator(
+                    ArithmeticNodeKind::Not(Box::new(operand)),
+                    span,
+                    tok.span.clone(),
+                ))
+            }
+            LexemeKind::If => self.if_call(tok),
+            LexemeKind::OpenBracket => self.interval(tok),
+            kind => match BinOp::from_lexeme(kind) {
+                Some(op) => Err(ParserError::InvalidUnaryOperator(op, tok.span.clone())),
+                None => Err(ParserError::UnexpectedToken(tok.clone())),
+            },
+        }
+    }
+
+    ///
+    /// Consume the parenthesis closing `open`
+    ///
+    fn close_paren(&mut self, open: &Lexeme) -> Result<&'a Lexeme, ParserError> {
+        match self.advance() {
+            Some(close @ tokof!(CloseParen)) => Ok(close),
+            Some(other) => Err(ParserError::UnexpectedToken(other.clone())),
+            None => Err(ParserError::UnclosedParen(open.span.clone())),
+        }
+    }
+
+    ///
+    /// Parse the arguments up to the parenthesis closing `open`, which has
+    /// already been consumed
+    ///
+    fn arguments(
+        &mut self,
+        open: &Lexeme,
+    ) -> Result<(Vec<ArithmeticNode>, &'a Lexeme), ParserError> {
+        let mut args = Vec::new();
+
+        if let Some(close @ tokof!(CloseParen)) = self.peek() {
+            self.advance();
+            return Ok((args, close));
+        }
+
+        loop {
+            args.push(self.expression(0)?);
+
+            match self.peek() {
+                Some(tokof!(Comma)) => {
+                    self.advance();
                 }
+                _ => return Ok((args, self.close_paren(open)?)),
             }
         }
     }
+
+    ///
+    /// Parse the arguments of a function invocation, the name and opening
+    /// parenthesis have already been consumed
+    ///
+    fn call(&mut self, name_tok: &Lexeme, open: &Lexeme) -> Result<ArithmeticNode, ParserError> {
+        let (args, close) = self.arguments(open)?;
+        let span = name_tok.span.up_to(&close.span);
+
+        let LexemeKind::Word(name) = &name_tok.kind else {
+            return Err(ParserError::UnexpectedToken(name_tok.clone()));
+        };
+
+        let kind = ArithmeticNodeKind::Call {
+            name: name.clone(),
+            args,
+        };
+
+        Ok(ArithmeticNode::new(kind, span))
+    }
+
+    ///
+    /// Parse `if(cond, then, otherwise)`, the `if` has already been consumed
+    ///
+    fn if_call(&mut self, if_tok: &Lexeme) -> Result<ArithmeticNode, ParserError> {
+        let Some(open @ tokof!(OpenParen)) = self.peek() else {
+            return Err(ParserError::ConditionalArguments(if_tok.span.clone()));
+        };
+        self.advance();
+
+        let (args, close) = self.arguments(open)?;
+        let Ok([cond, then, otherwise]) = <[ArithmeticNode; 3]>::try_from(args) else {
+            return Err(ParserError::ConditionalArguments(if_tok.span.clone()));
+        };
+
+        let kind = ArithmeticNodeKind::Conditional {
+            cond: Box::new(cond),
+            then: Box::new(then),
+            otherwise: Box::new(otherwise),
+        };
+        Ok(ArithmeticNode::with_operator(
+            kind,
+            if_tok.span.up_to(&close.span),
+            if_tok.span.clone(),
+        ))
+    }
+
+    ///
+    /// Parse `[lo, hi]`, the opening bracket has already been consumed
+    ///
+    fn interval(&mut self, open: &Lexeme) -> Result<ArithmeticNode, ParserError> {
+        let lo = self.expression(0)?;
+        match self.advance() {
+            Some(tokof!(Comma)) => (),
+            Some(other) => return Err(ParserError::UnexpectedToken(other.clone())),
+            None => return Err(ParserError::UnclosedBracket(open.span.clone())),
+        }
+
+        let hi = self.expression(0)?;
+        let close = match self.advance() {
+            Some(close @ tokof!(CloseBracket)) => close,
+            Some(other) => return Err(ParserError::UnexpectedToken(other.clone())),
+            None => return Err(ParserError::UnclosedBracket(open.span.clone())),
+        };
+
+        let kind = ArithmeticNodeKind::Interval {
+            lo: Box::new(lo),
+            hi: Box::new(hi),
+        };
+        Ok(ArithmeticNode::new(kind, open.span.up_to(&close.span)))
+    }
+
+    ///
+    /// Parse the branches of `cond ? then : otherwise`, the `?` has already been consumed
+    ///
+    fn conditional(
+        &mut self,
+        cond: ArithmeticNode,
+        question: &Lexeme,
+        r_bp: u8,
+    ) -> Result<ArithmeticNode, ParserError> {
+        let then = self.expression(0)?;
+
+        match self.advance() {
+            Some(tokof!(Colon)) => (),
+            Some(other) => return Err(ParserError::UnexpectedToken(other.clone())),
+            None => return Err(ParserError::MissingColon(question.span.clone())),
+        }
+
+        let otherwise = self.expression(r_bp)?;
+        let span = cond.span.up_to(&otherwise.span);
+        let kind = ArithmeticNodeKind::Conditional {
+            cond: Box::new(cond),
+            then: Box::new(then),
+            otherwise: Box::new(otherwise),
+        };
+        Ok(ArithmeticNode::with_operator(
+            kind,
+            span,
+            question.span.clone(),
+        ))
+    }
+}
+
+impl ArithmeticNode {
+    ///
+    /// Parse an expression
+    ///
+    pub fn parse(expr: &[Lexeme]) -> Result<ArithmeticNode, ParserError> {
+        ArithmeticNode::parse_following(expr, &Span::default())
+    }
+
+    ///
+    /// Parse an expression that directly follows the code at `prev`
+    ///
+    /// `prev` is only used to locate the error when `expr` is empty
+    ///
+    pub fn parse_following(expr: &[Lexeme], prev: &Span) -> Result<ArithmeticNode, ParserError> {
+        ArithmeticNode::parse_with(expr, prev, &ParseOptions::default())
+    }
+
+    ///
+    /// Like [`ArithmeticNode::parse_following`], with explicit parser settings
+    ///
+    pub fn parse_with(
+        expr: &[Lexeme],
+        prev: &Span,
+        options: &ParseOptions,
+    ) -> Result<ArithmeticNode, ParserError> {
+        let mut parser = Parser::new(expr, prev, options);
+        let node = parser.expression(0)?;
+
+        // The expression ended before the input did, e.g `2 3` or `1 + 2)`
+        if let Some(tok) = parser.peek() {
+            return Err(ParserError::UnexpectedToken(tok.clone()));
+        }
+
+        Ok(node)
+    }
 }
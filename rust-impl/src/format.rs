@@ -0,0 +1,262 @@
+//!
+//! Display of values with a chosen notation and separators, see [`Format`]
+//!
+//! Exact values are rounded exactly, floats from the shortest decimal that reads back as them,
+//! and the bounds of intervals outward so that the displayed interval still holds the value
+//!
+
+use std::str::FromStr;
+
+use bigdecimal::{BigDecimal, RoundingMode};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Signed, Zero};
+
+use crate::units;
+use crate::value::Value;
+
+///
+/// How the digits of a number are laid out
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Notation{
+    /// As values display by default, floats with the fewest digits that read back as them
+    #[default]
+    Auto,
+    /// A count of digits after the decimal separator, e.g `3.14` with 2
+    Fixed(u32),
+    /// A count of significant digits, at least one, e.g `3.14` or `1230` with 3
+    Significant(u32),
+    /// A single digit before the decimal separator and a power of ten, with a count of
+    /// significant digits, e.g `1.23e4` with 3
+    Scientific(u32),
+    /// Like scientific, with a power of ten that is a multiple of 3, e.g `12.3e3` with 3
+    Engineering(u32)
+}
+
+///
+/// How values are written, shared by every frontend so that a value looks the same everywhere
+///
+/// The default writes values as their `Display` does
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Format{
+    pub notation : Notation,
+    /// Between the integer and fractional digits
+    pub decimal_separator : char,
+    /// Between the groups of three digits of the integer part, e.g the `,` of `1,234,567`
+    pub grouping : Option<char>
+}
+
+impl Default for Format{
+    fn default() -> Self{
+        Self{
+            notation: Notation::Auto,
+            decimal_separator: '.',
+            grouping: None
+        }
+    }
+}
+
+impl Format{
+    pub fn new(notation : Notation) -> Self{
+        Self{ notation, ..Self::default() }
+    }
+
+    ///
+    /// The separators of the locale `tag`, e.g `de` or `fr-CA`, with thousands grouped
+    ///
+    /// Only the language of the tag counts, `None` for a language this does not know
+    ///
+    pub fn locale(tag : &str) -> Option<Self>{
+        let language = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+        let (decimal_separator, grouping) = match language.as_str(){
+            "en" | "ja" | "ko" | "zh" | "he" | "th" => ('.', ','),
+            "de" | "da" | "el" | "es" | "id" | "it" | "nl" | "pt" | "tr" => (',', '.'),
+            "fr" => (',', '\u{202f}'),
+            "cs" | "fi" | "nb" | "pl" | "ru" | "sv" | "uk" => (',', '\u{a0}'),
+            _ => return None
+        };
+        Some(Self{ notation: Notation::Auto, decimal_separator, grouping: Some(grouping) })
+    }
+
+    pub(crate) fn value(&self, value : &Value) -> String{
+        let rounding = RoundingMode::HalfUp;
+        match value{
+            Value::Number(n) => self.float(*n, rounding),
+            Value::Integer(i) => self.real(value, || Real::Decimal(BigDecimal::from(i.clone())), rounding),
+            Value::Rational(r) => self.real(value, || Real::Rational(r), rounding),
+            Value::Decimal(d) => self.real(value, || Real::Decimal(d.clone()), rounding),
+            Value::Complex(z) => {
+                if z.re == 0.0{
+                    format!("{}i", self.float(z.im, rounding))
+                }
+                else if z.im.is_sign_negative(){
+                    format!("{}-{}i", self.float(z.re, rounding), self.float(-z.im, rounding))
+                }
+                else{
+                    format!("{}+{}i", self.float(z.re, rounding), self.float(z.im, rounding))
+                }
+            },
+            Value::Interval(i) => {
+                // A comma would also separate the bounds
+                let separator = if self.decimal_separator == ',' || self.grouping == Some(',') { ";" } else { "," };
+                format!("[{}{separator} {}]", self.float(i.lo(), RoundingMode::Floor), self.float(i.hi(), RoundingMode::Ceiling))
+            },
+            Value::Measurement(m) => format!("{} ± {}", self.float(m.value(), rounding), self.float(m.sigma(), rounding)),
+            Value::Quantity(q) => units::with_unit(&self.value(&q.magnitude), &q.unit),
+            Value::Bool(b) => b.to_string()
+        }
+    }
+
+    fn float(&self, x : f64, rounding : RoundingMode) -> String{
+        if self.notation == Notation::Auto || !x.is_finite(){
+            return self.separate(&x.to_string());
+        }
+        let shortest = BigDecimal::from_str(&format!("{x:e}")).expect("finite floats are decimals");
+        self.separate(&self.notate(&Real::Decimal(shortest), rounding))
+    }
+
+    ///
+    /// Write an exact number, which displays as `value` in the automatic notation
+    ///
+    fn real<'a>(&self, value : &Value, exact : impl FnOnce() -> Real<'a>, rounding : RoundingMode) -> String{
+        if self.notation == Notation::Auto{
+            // Fractions that have no finite decimal expansion stay fractions
+            return value.to_string().split('/').map(|part| self.separate(part)).collect::<Vec<_>>().join("/");
+        }
+        self.separate(&self.notate(&exact(), rounding))
+    }
+
+    fn notate(&self, x : &Real, rounding : RoundingMode) -> String{
+        match self.notation{
+            Notation::Auto => unreachable!("the automatic notation is the display of the value"),
+            Notation::Fixed(decimals) => x.round(decimals.into(), rounding).to_plain_string(),
+            Notation::Significant(digits) => significant(x, digits, rounding).to_plain_string(),
+            Notation::Scientific(digits) => exponential(x, digits, 1, rounding),
+            Notation::Engineering(digits) => exponential(x, digits, 3, rounding)
+        }
+    }
+
+    ///
+    /// Replace the separators of a number written with `.` and no grouping, e.g `-1234.5e6`
+    ///
+    fn separate(&self, number : &str) -> String{
+        let (sign, rest) = number.strip_prefix('-').map_or(("", number), |rest| ("-", rest));
+        let (mantissa, exponent) = rest.split_at(rest.find(['e', 'E']).unwrap_or(rest.len()));
+        let (int, fraction) = mantissa.split_at(mantissa.find('.').unwrap_or(mantissa.len()));
+
+        let mut separated = sign.to_string();
+        match self.grouping{
+            // Not `inf` nor `NaN`
+            Some(grouping) if int.bytes().all(|b| b.is_ascii_digit()) => {
+                for (index, digit) in int.chars().enumerate(){
+                    if index > 0 && (int.len() - index) % 3 == 0{
+                        separated.push(grouping);
+                    }
+                    separated.push(digit);
+                }
+            },
+            _ => separated.push_str(int)
+        }
+        if let Some(fraction) = fraction.strip_prefix('.'){
+            separated.push(self.decimal_separator);
+            separated.push_str(fraction);
+        }
+        separated.push_str(exponent);
+        separated
+    }
+}
+
+///
+/// A number to write, exact so that rounding it for display is exact
+///
+enum Real<'a>{
+    Decimal(BigDecimal),
+    Rational(&'a BigRational)
+}
+
+impl Real<'_>{
+    ///
+    /// `⌊log10 |x|⌋`, 0 for zero
+    ///
+    fn magnitude(&self) -> i64{
+        match self{
+            Self::Decimal(d) => d.order_of_magnitude(),
+            Self::Rational(r) if r.is_zero() => 0,
+            Self::Rational(r) => {
+                let (numer, denom) = (r.numer().abs(), r.denom());
+                let at_least = |m : i64| if m >= 0 { numer >= denom * power_of_ten(m) } else { &numer * power_of_ten(-m) >= *denom };
+                // Within one of the difference of the digit counts
+                let mut magnitude = numer.to_string().len() as i64 - denom.to_string().len() as i64;
+                while !at_least(magnitude){
+                    magnitude -= 1;
+                }
+                while at_least(magnitude + 1){
+                    magnitude += 1;
+                }
+                magnitude
+            }
+        }
+    }
+
+    ///
+    /// Rounded to `scale` digits after the decimal point, a negative scale rounds to tens, hundreds...
+    ///
+    fn round(&self, scale : i64, rounding : RoundingMode) -> BigDecimal{
+        match self{
+            Self::Decimal(d) => d.with_scale_round(scale, rounding),
+            Self::Rational(r) => {
+                let shifted = if scale >= 0{
+                    *r * BigRational::from_integer(power_of_ten(scale))
+                }
+                else{
+                    *r / BigRational::from_integer(power_of_ten(-scale))
+                };
+                let integer = match rounding{
+                    RoundingMode::Floor => shifted.floor(),
+                    RoundingMode::Ceiling => shifted.ceil(),
+                    // Halves away from zero, like RoundingMode::HalfUp
+                    _ => shifted.round()
+                };
+                BigDecimal::new(integer.to_integer(), scale)
+            }
+        }
+    }
+}
+
+fn power_of_ten(exponent : i64) -> BigInt{
+    num_traits::pow(BigInt::from(10), exponent as usize)
+}
+
+///
+/// `x` rounded to `digits` significant digits, keeping the zeros among them, e.g `1.50`
+///
+fn significant(x : &Real, digits : u32, rounding : RoundingMode) -> BigDecimal{
+    let digits = i64::from(digits.max(1));
+    let magnitude = x.magnitude();
+    let mut rounded = x.round(digits - 1 - magnitude, rounding);
+    // Rounding up to the next power of ten adds a digit, e.g 9.99 to 10.0
+    if !rounded.is_zero() && rounded.order_of_magnitude() > magnitude{
+        rounded = rounded.with_scale(digits - 2 - magnitude);
+    }
+    // Digits before the decimal point are written out, e.g 1230 rather than 123e1
+    if rounded.as_bigint_and_exponent().1 < 0{
+        rounded = rounded.with_scale(0);
+    }
+    rounded
+}
+
+///
+/// `x` as `digits` significant digits times a power of ten that is a multiple of `step`
+///
+fn exponential(x : &Real, digits : u32, step : i64, rounding : RoundingMode) -> String{
+    let rounded = significant(x, digits, rounding);
+    let magnitude = if rounded.is_zero() { 0 } else { rounded.order_of_magnitude() };
+    let exponent = magnitude.div_euclid(step) * step;
+
+    let (int, scale) = rounded.as_bigint_and_exponent();
+    let mantissa = BigDecimal::new(int, scale + exponent);
+    let decimals = (i64::from(digits.max(1)) - 1 - (magnitude - exponent)).max(0);
+    format!("{}e{exponent}", mantissa.with_scale(decimals).to_plain_string())
+}
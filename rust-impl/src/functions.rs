@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use crate::angle::AngleMode;
+use crate::evaluator::{NumericMode, Value};
+use crate::expression::ArithmeticNode;
+use crate::lexer::is_identifier;
+
+///
+/// How many arguments a function accepts
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arity{
+    Fixed(usize),
+    /// Between the two bounds, inclusive
+    Range(usize, usize),
+    /// Any count of at least `min`
+    Variadic{ min : usize }
+}
+
+impl Arity{
+    pub fn accepts(&self, count : usize) -> bool{
+        match *self{
+            Self::Fixed(n) => count == n,
+            Self::Range(min, max) => (min..=max).contains(&count),
+            Self::Variadic { min } => count >= min
+        }
+    }
+}
+
+impl std::fmt::Display for Arity{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        match self{
+            Self::Fixed(n) => write!(f, "{n}"),
+            Self::Range(min, max) => write!(f, "{min} to {max}"),
+            Self::Variadic { min } => write!(f, "at least {min}")
+        }
+    }
+}
+
+///
+/// Why a function could not produce a value from its arguments
+///
+/// The evaluator attaches the location of the call before reporting it
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionError{
+    LogZero,
+    /// An argument lies outside the domain of the function, the message says which
+    Domain(String),
+    /// The argument at `index` has a type the function does not accept
+    ArgumentType{
+        index : usize,
+        expected : &'static str,
+        found : &'static str
+    }
+}
+
+///
+/// What a native function may depend on besides its arguments
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallContext{
+    /// Mode of the calling evaluator, decimal results are rounded to its precision
+    pub numeric_mode : NumericMode,
+    /// Unit of the angles trigonometric functions take and give
+    pub angle_mode : AngleMode
+}
+
+pub type NativeFn = Box<dyn Fn(&[Value], &CallContext) -> Result<Value, FunctionError> + Send + Sync>;
+
+///
+/// A function callable from expressions
+///
+pub struct Function{
+    pub arity : Arity,
+    /// Only ever called with an argument count accepted by `arity`
+    pub implementation : NativeFn
+}
+
+impl std::fmt::Debug for Function{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        f.debug_struct("Function").field("arity", &self.arity).finish_non_exhaustive()
+    }
+}
+
+///
+/// Convert the arguments of a function of numbers to floats
+///
+pub(crate) fn number_args(args : &[Value]) -> Result<Vec<f64>, FunctionError>{
+    args.iter()
+        .enumerate()
+        .map(|(index, arg)| arg.as_number().ok_or(FunctionError::ArgumentType{
+            index,
+            expected: "number",
+            found: arg.type_name()
+        }))
+        .collect()
+}
+
+///
+/// A function defined in the expression language, e.g `f(x, y) = x^2 + y`
+///
+/// Within the body the parameters shadow the variables of the evaluator
+///
+#[derive(Debug, Clone)]
+pub struct UserFunction{
+    pub params : Vec<String>,
+    pub body : ArithmeticNode
+}
+
+impl UserFunction{
+    pub fn arity(&self) -> Arity{
+        Arity::Fixed(self.params.len())
+    }
+}
+
+///
+/// The name given to [`FunctionRegistry::register`] is not an identifier, so it could never be called
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidFunctionName(pub String);
+
+impl std::fmt::Display for InvalidFunctionName{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "Invalid function name: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidFunctionName{}
+
+///
+/// The functions known to an [`Evaluator`](crate::evaluator::Evaluator), by name
+///
+#[derive(Debug, Default)]
+pub struct FunctionRegistry{
+    functions : HashMap<String, Function>
+}
+
+impl FunctionRegistry{
+    ///
+    /// A registry without any function
+    ///
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    ///
+    /// A registry holding the built-in functions
+    ///
+    pub fn with_builtins() -> Self{
+        let mut registry = Self::new();
+        crate::builtins::register(&mut registry);
+        registry
+    }
+
+    ///
+    /// Add a function of numbers, replacing any previous one of the same name
+    ///
+    /// Calling it with an argument of another type is an error
+    ///
+    pub fn register<F>(&mut self, name : &str, arity : Arity, implementation : F) -> Result<(), InvalidFunctionName>
+        where F : Fn(&[f64]) -> Result<f64, FunctionError> + Send + Sync + 'static
+    {
+        self.register_typed(name, arity, move |args, _| implementation(&number_args(args)?).map(Value::Number))
+    }
+
+    ///
+    /// Add a function taking and returning values of any type, replacing any previous one of the same name
+    ///
+    pub fn register_typed<F>(&mut self, name : &str, arity : Arity, implementation : F) -> Result<(), InvalidFunctionName>
+        where F : Fn(&[Value], &CallContext) -> Result<Value, FunctionError> + Send + Sync + 'static
+    {
+        if !is_identifier(name){
+            return Err(InvalidFunctionName(name.to_string()));
+        }
+
+        self.functions.insert(name.to_string(), Function{
+            arity,
+            implementation: Box::new(implementation)
+        });
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name : &str) -> Option<Function>{
+        self.functions.remove(name)
+    }
+
+    pub fn get(&self, name : &str) -> Option<&Function>{
+        self.functions.get(name)
+    }
+
+    pub fn contains(&self, name : &str) -> bool{
+        self.functions.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str>{
+        self.functions.keys().map(String::as_str)
+    }
+}
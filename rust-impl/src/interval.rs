@@ -0,0 +1,580 @@
+//!
+//! Intervals of real numbers with guaranteed bounds, see [`Interval`]
+//!
+//! Bounds are rounded outward. The arithmetic operators and the square root round each bound exactly in its
+//! direction, using the rounding error of the nearest result, which is itself a float. The other functions come
+//! from the platform math library, which is not correctly rounded, so their bounds are moved out by one more float
+//!
+
+use std::cmp::Ordering;
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+use num_rational::BigRational;
+use num_traits::ToPrimitive;
+
+use crate::expression::BinOp;
+use crate::value::OperationError;
+
+/// Magnitude below which products and quotients may lose their rounding error to underflow
+const TINY : f64 = f64::MIN_POSITIVE * (1u64 << 54) as f64;
+
+/// Magnitude from which consecutive floats are further apart than a period of the trigonometric functions
+const HUGE_ANGLE : f64 = (1u64 << 52) as f64;
+
+///
+/// The real numbers between `lo` and `hi`, both included, e.g `[1.9, 2.1]`
+///
+/// The bounds may be infinite, but never NaN, and `lo` is never above `hi`
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval{
+    lo : f64,
+    hi : f64
+}
+
+impl Interval{
+    const ENTIRE : Self = Self{ lo: f64::NEG_INFINITY, hi: f64::INFINITY };
+
+    ///
+    /// The numbers from `lo` to `hi`, or `None` if a bound is NaN or `lo` is above `hi`
+    ///
+    pub fn new(lo : f64, hi : f64) -> Option<Self>{
+        (lo <= hi).then_some(Self{ lo, hi })
+    }
+
+    ///
+    /// The interval holding only `x`, which must not be NaN
+    ///
+    pub fn point(x : f64) -> Self{
+        assert!(!x.is_nan(), "an interval bound cannot be NaN");
+        Self{ lo: x, hi: x }
+    }
+
+    pub fn lo(&self) -> f64{
+        self.lo
+    }
+
+    pub fn hi(&self) -> f64{
+        self.hi
+    }
+
+    ///
+    /// The middle of the interval, rounded to the nearest float
+    ///
+    pub fn mid(&self) -> f64{
+        if self.lo == self.hi{
+            return self.lo;
+        }
+        // Halved first, so that the sum cannot overflow
+        self.lo / 2.0 + self.hi / 2.0
+    }
+
+    ///
+    /// The distance between the bounds, rounded up
+    ///
+    pub fn width(&self) -> f64{
+        sub(self.hi, self.lo, true)
+    }
+
+    pub fn contains(&self, x : f64) -> bool{
+        self.lo <= x && x <= self.hi
+    }
+
+    ///
+    /// The narrowest interval holding `r`
+    ///
+    pub fn enclosing(r : &BigRational) -> Self{
+        let nearest = r.to_f64().unwrap_or(f64::NAN);
+        if nearest.is_nan(){
+            return Self::ENTIRE;
+        }
+        if nearest.is_infinite(){
+            // Beyond the largest float
+            return if nearest > 0.0 { Self{ lo: f64::MAX, hi: nearest } } else { Self{ lo: nearest, hi: -f64::MAX } };
+        }
+
+        match BigRational::from_float(nearest).map(|n| n.cmp(r)){
+            Some(Ordering::Equal) => Self::point(nearest),
+            Some(Ordering::Greater) => Self{ lo: nearest.next_down(), hi: nearest },
+            Some(Ordering::Less) => Self{ lo: nearest, hi: nearest.next_up() },
+            None => Self::around(nearest)
+        }
+    }
+
+    ///
+    /// The interval from the float below `x` to the float above it, for a value known to be nearest to `x`
+    ///
+    pub(crate) fn around(x : f64) -> Self{
+        Self{ lo: x.next_down(), hi: x.next_up() }
+    }
+
+    ///
+    /// The interval holding π, the nearest float is below it
+    ///
+    pub(crate) fn pi() -> Self{
+        Self{ lo: PI, hi: PI.next_up() }
+    }
+
+    ///
+    /// The interval holding e, the nearest float is below it
+    ///
+    pub(crate) fn e() -> Self{
+        Self{ lo: std::f64::consts::E, hi: std::f64::consts::E.next_up() }
+    }
+
+    fn contains_zero(&self) -> bool{
+        self.contains(0.0)
+    }
+
+    pub(crate) fn negate(&self) -> Self{
+        Self{ lo: -self.hi, hi: -self.lo }
+    }
+
+    fn add(&self, other : &Self) -> Self{
+        Self{ lo: add(self.lo, other.lo, false), hi: add(self.hi, other.hi, true) }
+    }
+
+    fn sub(&self, other : &Self) -> Self{
+        self.add(&other.negate())
+    }
+
+    fn mul(&self, other : &Self) -> Self{
+        let pairs = [(self.lo, other.lo), (self.lo, other.hi), (self.hi, other.lo), (self.hi, other.hi)];
+        Self{
+            lo: pairs.iter().map(|&(x, y)| mul(x, y, false)).fold(f64::INFINITY, f64::min),
+            hi: pairs.iter().map(|&(x, y)| mul(x, y, true)).fold(f64::NEG_INFINITY, f64::max)
+        }
+    }
+
+    fn div(&self, other : &Self) -> Result<Self, OperationError>{
+        if other.contains_zero(){
+            return Err(OperationError::IntervalDivision);
+        }
+        let pairs = [(self.lo, other.lo), (self.lo, other.hi), (self.hi, other.lo), (self.hi, other.hi)];
+        Ok(Self{
+            lo: pairs.iter().map(|&(x, y)| div(x, y, false)).fold(f64::INFINITY, f64::min),
+            hi: pairs.iter().map(|&(x, y)| div(x, y, true)).fold(f64::NEG_INFINITY, f64::max)
+        })
+    }
+
+    ///
+    /// `self` to the power of `n`, by multiplication of the bounds
+    ///
+    fn powi(&self, n : i64) -> Result<Self, OperationError>{
+        if n < 0{
+            return Self::point(1.0).div(&self.powi(-n)?);
+        }
+        let n = n.unsigned_abs();
+        if n % 2 == 1{
+            return Ok(Self{ lo: signed_pow(self.lo, n, false), hi: signed_pow(self.hi, n, true) });
+        }
+        // Even powers only depend on the distance to zero
+        let (near, far) = (self.lo.abs().min(self.hi.abs()), self.lo.abs().max(self.hi.abs()));
+        let near = if self.contains_zero() { 0.0 } else { near };
+        Ok(Self{ lo: pow(near, n, false), hi: pow(far, n, true) })
+    }
+
+    ///
+    /// `self` to the power of `exponent`. Unless the exponent is a single integer the base must
+    /// not hold negative numbers, nor zero with an exponent that may not be positive
+    ///
+    fn pow(&self, exponent : &Self) -> Result<Self, OperationError>{
+        if exponent.lo == exponent.hi && exponent.lo.fract() == 0.0 && exponent.lo.abs() < i64::MAX as f64{
+            return self.powi(exponent.lo as i64);
+        }
+        if self.lo > 0.0{
+            return Ok(exponent.mul(&self.ln_positive()).exp());
+        }
+        if self.lo == 0.0 && exponent.lo > 0.0{
+            // Increasing in the base, so the largest power is that of the upper bound
+            let hi = if self.hi == 0.0 { 0.0 } else { exponent.mul(&Self::point(self.hi).ln_positive()).exp().hi };
+            return Ok(Self{ lo: 0.0, hi });
+        }
+        Err(OperationError::IntervalPower)
+    }
+
+    ///
+    /// Apply an increasing function of the math library to the bounds, each moved out by one float
+    ///
+    fn increasing(&self, f : fn(f64) -> f64) -> Self{
+        Self{ lo: f(self.lo).next_down(), hi: f(self.hi).next_up() }
+    }
+
+    ///
+    /// Apply a decreasing function of the math library to the bounds, each moved out by one float
+    ///
+    fn decreasing(&self, f : fn(f64) -> f64) -> Self{
+        Self{ lo: f(self.hi).next_down(), hi: f(self.lo).next_up() }
+    }
+
+    ///
+    /// Keep the bounds within `[min, max]`, the range of the function they come from
+    ///
+    fn within(self, min : f64, max : f64) -> Self{
+        Self{ lo: self.lo.clamp(min, max), hi: self.hi.clamp(min, max) }
+    }
+
+    ///
+    /// Whether the interval may hold `point` plus a multiple of `period`
+    ///
+    /// The multiples are computed on floats, so points slightly outside of the interval are
+    /// counted in, which only widens the results depending on it
+    ///
+    fn reaches(&self, point : f64, period : f64) -> bool{
+        let slack = 8.0 * f64::EPSILON * (1.0 + self.lo.abs().max(self.hi.abs()));
+        let k = ((self.lo - point) / period).floor();
+        [k, k + 1.0].iter().any(|k| {
+            let candidate = point + k * period;
+            self.lo - slack <= candidate && candidate <= self.hi + slack
+        })
+    }
+
+    ///
+    /// A function of period 2π reaching 1 at `maximum` and -1 at `minimum`, monotonic in between
+    ///
+    fn periodic(&self, f : fn(f64) -> f64, maximum : f64, minimum : f64) -> Self{
+        if self.width() >= TAU || self.lo.abs().max(self.hi.abs()) >= HUGE_ANGLE{
+            return Self{ lo: -1.0, hi: 1.0 };
+        }
+        let (a, b) = (f(self.lo), f(self.hi));
+        Self{
+            lo: if self.reaches(minimum, TAU) { -1.0 } else { a.min(b).next_down() },
+            hi: if self.reaches(maximum, TAU) { 1.0 } else { a.max(b).next_up() }
+        }.within(-1.0, 1.0)
+    }
+
+    pub(crate) fn sin(&self) -> Self{
+        self.periodic(f64::sin, FRAC_PI_2, -FRAC_PI_2)
+    }
+
+    pub(crate) fn cos(&self) -> Self{
+        self.periodic(f64::cos, 0.0, PI)
+    }
+
+    ///
+    /// `None` if the interval may hold an odd multiple of π/2, where tan is undefined
+    ///
+    pub(crate) fn tan(&self) -> Option<Self>{
+        if self.width() >= PI || self.lo.abs().max(self.hi.abs()) >= HUGE_ANGLE || self.reaches(FRAC_PI_2, PI){
+            return None;
+        }
+        Some(self.increasing(f64::tan))
+    }
+
+    pub(crate) fn asin(&self) -> Option<Self>{
+        (self.lo >= -1.0 && self.hi <= 1.0).then(|| self.increasing(f64::asin))
+    }
+
+    pub(crate) fn acos(&self) -> Option<Self>{
+        (self.lo >= -1.0 && self.hi <= 1.0).then(|| self.decreasing(f64::acos).within(0.0, f64::INFINITY))
+    }
+
+    pub(crate) fn atan(&self) -> Self{
+        self.increasing(f64::atan)
+    }
+
+    pub(crate) fn sinh(&self) -> Self{
+        self.increasing(f64::sinh)
+    }
+
+    pub(crate) fn cosh(&self) -> Self{
+        if self.contains_zero(){
+            let far = self.lo.abs().max(self.hi.abs());
+            return Self{ lo: 1.0, hi: far.cosh().next_up() };
+        }
+        let near = if self.lo > 0.0 { *self } else { self.negate() };
+        near.increasing(f64::cosh).within(1.0, f64::INFINITY)
+    }
+
+    pub(crate) fn tanh(&self) -> Self{
+        self.increasing(f64::tanh).within(-1.0, 1.0)
+    }
+
+    pub(crate) fn asinh(&self) -> Self{
+        self.increasing(f64::asinh)
+    }
+
+    pub(crate) fn acosh(&self) -> Option<Self>{
+        (self.lo >= 1.0).then(|| self.increasing(f64::acosh).within(0.0, f64::INFINITY))
+    }
+
+    pub(crate) fn atanh(&self) -> Option<Self>{
+        (self.lo > -1.0 && self.hi < 1.0).then(|| self.increasing(f64::atanh))
+    }
+
+    pub(crate) fn sqrt(&self) -> Option<Self>{
+        (self.lo >= 0.0).then(|| Self{ lo: sqrt(self.lo, false), hi: sqrt(self.hi, true) })
+    }
+
+    pub(crate) fn cbrt(&self) -> Self{
+        self.increasing(f64::cbrt)
+    }
+
+    pub(crate) fn exp(&self) -> Self{
+        self.increasing(f64::exp).within(0.0, f64::INFINITY)
+    }
+
+    ///
+    /// The natural logarithm of an interval of positive numbers
+    ///
+    fn ln_positive(&self) -> Self{
+        self.increasing(f64::ln)
+    }
+
+    ///
+    /// `None` if the interval holds zero or negative numbers
+    ///
+    pub(crate) fn ln(&self) -> Option<Self>{
+        (self.lo > 0.0).then(|| self.ln_positive())
+    }
+
+    pub(crate) fn log10(&self) -> Option<Self>{
+        (self.lo > 0.0).then(|| self.increasing(f64::log10))
+    }
+
+    pub(crate) fn log2(&self) -> Option<Self>{
+        (self.lo > 0.0).then(|| self.increasing(f64::log2))
+    }
+
+    ///
+    /// The logarithm in base `base`, `None` if either interval holds zero or negative numbers, or the base may be 1
+    ///
+    pub(crate) fn log(&self, base : &Self) -> Option<Self>{
+        self.ln()?.div(&base.ln()?).ok()
+    }
+
+    ///
+    /// The `n`th root, `None` if `n` may be zero, or the interval holds negative numbers and `n` is not a single odd integer
+    ///
+    pub(crate) fn nrt(&self, n : &Self) -> Option<Self>{
+        let inverse = Self::point(1.0).div(n).ok()?;
+        if self.lo >= 0.0{
+            return self.pow(&inverse).ok();
+        }
+        if n.lo != n.hi || n.lo.fract() != 0.0 || n.lo % 2.0 == 0.0{
+            return None;
+        }
+        // Odd roots are increasing, and as odd as the powers they undo
+        let root = |x : f64| if x < 0.0 { Self::point(-x).pow(&inverse).map(|r| r.negate()) } else { Self::point(x).pow(&inverse) };
+        Some(Self{ lo: root(self.lo).ok()?.lo, hi: root(self.hi).ok()?.hi })
+    }
+
+    pub(crate) fn hypot(&self, other : &Self) -> Self{
+        let square = |x : &Self| x.powi(2).expect("squares are positive powers");
+        square(self).add(&square(other)).sqrt().expect("squares are not negative")
+    }
+
+    ///
+    /// The angle of the point (`x`, `self`) from the positive x axis, between -π and π
+    ///
+    /// Points on both sides of the negative x axis have angles near -π and π, so the whole range is returned for them
+    ///
+    pub(crate) fn atan2(&self, x : &Self) -> Self{
+        let y = self;
+        let whole = Self{ lo: -Self::pi().hi, hi: Self::pi().hi };
+        let half_pi = Self::pi().mul(&Self::point(0.5));
+        let angle = if x.lo > 0.0{
+            y.div(x).expect("x does not hold zero").atan()
+        }
+        // Away from the x axis, measured from the y axis instead
+        else if y.lo > 0.0{
+            half_pi.sub(&x.div(y).expect("y does not hold zero").atan())
+        }
+        else if y.hi < 0.0{
+            half_pi.negate().sub(&x.div(y).expect("y does not hold zero").atan())
+        }
+        // On the negative x axis
+        else if y.lo == 0.0 && y.hi == 0.0 && x.hi < 0.0{
+            Self::pi()
+        }
+        else{
+            whole
+        };
+        angle.within(whole.lo, whole.hi)
+    }
+
+    pub(crate) fn abs(&self) -> Self{
+        let (near, far) = (self.lo.abs().min(self.hi.abs()), self.lo.abs().max(self.hi.abs()));
+        Self{ lo: if self.contains_zero() { 0.0 } else { near }, hi: far }
+    }
+
+    ///
+    /// Apply a non decreasing function with exact results to the bounds, e.g `floor`
+    ///
+    pub(crate) fn exact(&self, f : fn(f64) -> f64) -> Self{
+        Self{ lo: f(self.lo), hi: f(self.hi) }
+    }
+
+    pub(crate) fn min(intervals : &[Self]) -> Self{
+        Self{
+            lo: intervals.iter().map(|i| i.lo).fold(f64::INFINITY, f64::min),
+            hi: intervals.iter().map(|i| i.hi).fold(f64::INFINITY, f64::min)
+        }
+    }
+
+    pub(crate) fn max(intervals : &[Self]) -> Self{
+        Self{
+            lo: intervals.iter().map(|i| i.lo).fold(f64::NEG_INFINITY, f64::max),
+            hi: intervals.iter().map(|i| i.hi).fold(f64::NEG_INFINITY, f64::max)
+        }
+    }
+}
+
+///
+/// Apply one of the arithmetic operators `+ - * / // % ^` to intervals
+///
+/// Dividing by an interval holding zero is an error rather than an unbounded result
+///
+pub(crate) fn arithmetic(op : BinOp, x : Interval, y : Interval) -> Result<Interval, OperationError>{
+    match op{
+        BinOp::Add => Ok(x.add(&y)),
+        BinOp::Sub => Ok(x.sub(&y)),
+        BinOp::Mul => Ok(x.mul(&y)),
+        BinOp::Div => x.div(&y),
+        BinOp::IntDiv => Ok(x.div(&y)?.exact(f64::floor)),
+        BinOp::Mod => {
+            let quotient = x.div(&y)?.exact(f64::floor);
+            if quotient.lo == quotient.hi{
+                return Ok(x.sub(&y.mul(&quotient)));
+            }
+            // The remainder goes through all of its range as the quotient changes
+            Ok(if y.lo > 0.0 { Interval{ lo: 0.0, hi: y.hi } } else { Interval{ lo: y.lo, hi: 0.0 } })
+        },
+        BinOp::Exp => x.pow(&y),
+        _ => unreachable!("{:?} is not an arithmetic operator", op.symbol())
+    }
+}
+
+///
+/// Order two intervals, `None` when they overlap, unless they are the same single number
+///
+pub(crate) fn compare(x : &Interval, y : &Interval) -> Option<Ordering>{
+    if x.hi < y.lo{
+        Some(Ordering::Less)
+    }
+    else if x.lo > y.hi{
+        Some(Ordering::Greater)
+    }
+    else if x.lo == x.hi && x == y{
+        Some(Ordering::Equal)
+    }
+    else{
+        None
+    }
+}
+
+///
+/// Round `nearest`, the result of an operation rounded to nearest, up or down
+///
+/// `error` is what `nearest` misses of the exact result, and `finite` whether the operands were finite,
+/// in which case an infinite result overflowed. A NaN result, e.g of opposite infinite bounds, bounds nothing
+///
+fn directed(nearest : f64, error : f64, finite : bool, up : bool) -> f64{
+    if nearest.is_nan(){
+        return if up { f64::INFINITY } else { f64::NEG_INFINITY };
+    }
+    if nearest.is_infinite() && finite{
+        return if (nearest > 0.0) == up { nearest } else { f64::MAX.copysign(nearest) };
+    }
+    if up && error > 0.0{
+        nearest.next_up()
+    }
+    else if !up && error < 0.0{
+        nearest.next_down()
+    }
+    else{
+        nearest
+    }
+}
+
+///
+/// `x + y` rounded in the direction of `up`, the error of the sum is found exactly by Knuth's TwoSum
+///
+fn add(x : f64, y : f64, up : bool) -> f64{
+    let sum = x + y;
+    let y_part = sum - x;
+    let error = (x - (sum - y_part)) + (y - y_part);
+    directed(sum, error, x.is_finite() && y.is_finite(), up)
+}
+
+fn sub(x : f64, y : f64, up : bool) -> f64{
+    add(x, -y, up)
+}
+
+///
+/// `x * y` rounded in the direction of `up`, the error of the product is found exactly by a fused multiply-add
+///
+fn mul(x : f64, y : f64, up : bool) -> f64{
+    // Zero absorbs infinite bounds, the bound stands for finite numbers approaching it
+    if x == 0.0 || y == 0.0{
+        return 0.0;
+    }
+    let product = x * y;
+    if product.abs() < TINY{
+        return if up { product.next_up() } else { product.next_down() };
+    }
+    directed(product, x.mul_add(y, -product), x.is_finite() && y.is_finite(), up)
+}
+
+///
+/// `x / y` rounded in the direction of `up`, `y` is not zero
+///
+fn div(x : f64, y : f64, up : bool) -> f64{
+    if x == 0.0{
+        return 0.0;
+    }
+    if x.is_infinite() && y.is_infinite(){
+        // Both bounds stand for numbers growing without limit, so their quotient is anywhere on its side of zero
+        let sign = x.signum() * y.signum();
+        return if (sign > 0.0) == up { f64::INFINITY.copysign(sign) } else { 0.0 };
+    }
+    let quotient = x / y;
+    if quotient.abs() < TINY{
+        return if up { quotient.next_up() } else { quotient.next_down() };
+    }
+    // The remainder `x - quotient * y` is exact, and has the sign of the error times that of `y`
+    let rest = -quotient.mul_add(y, -x);
+    directed(quotient, rest * y.signum(), x.is_finite() && y.is_finite(), up)
+}
+
+///
+/// The square root of `x` rounded in the direction of `up`
+///
+fn sqrt(x : f64, up : bool) -> f64{
+    let root = x.sqrt();
+    if x < TINY{
+        return if up { root.next_up() } else { root.next_down().max(0.0) };
+    }
+    directed(root, -root.mul_add(root, -x), true, up)
+}
+
+///
+/// `x` to the power of `n` rounded in the direction of `up`, `x` is not negative
+///
+fn pow(x : f64, n : u64, up : bool) -> f64{
+    // Squaring and multiplying non-negative numbers is increasing in them, so rounding each step keeps the bound
+    let (mut result, mut base, mut n) = (1.0, x, n);
+    while n > 0{
+        if n % 2 == 1{
+            result = mul(result, base, up);
+        }
+        n /= 2;
+        if n > 0{
+            base = mul(base, base, up);
+        }
+    }
+    result
+}
+
+///
+/// `x` to the odd power `n` rounded in the direction of `up`
+///
+fn signed_pow(x : f64, n : u64, up : bool) -> f64{
+    if x < 0.0 { -pow(-x, n, !up) } else { pow(x, n, up) }
+}
+
+impl std::fmt::Display for Interval{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "[{}, {}]", self.lo, self.hi)
+    }
+}
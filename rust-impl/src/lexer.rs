@@ -1,8 +1,55 @@
-#[derive(Debug, Clone)]
-#[allow(unused)]
+use unicode_width::UnicodeWidthChar;
+
+use crate::angle::AngleMode;
+
+///
+/// A location in the source text
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position{
+    /// Byte offset into the source
+    pub offset : usize,
+    /// Zero based line number
+    pub line : usize,
+    /// Zero based column, counted in characters
+    pub column : usize
+}
+
+impl Position{
+    pub fn new(offset : usize, line : usize, column : usize) -> Self{
+        Position{offset, line, column}
+    }
+
+    ///
+    /// The position just past the end of `source`
+    ///
+    pub fn end_of(source : &str) -> Self{
+        let mut pos = Position::default();
+        for c in source.chars(){
+            pos.advance(c);
+        }
+        pos
+    }
+
+    ///
+    /// Move past `c`, which must be the character found at this position
+    ///
+    pub fn advance(&mut self, c : char){
+        self.offset += c.len_utf8();
+        if c == '\n'{
+            self.line += 1;
+            self.column = 0;
+        }
+        else{
+            self.column += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Span{
-    begin : usize,
-    end : usize
+    pub begin : Position,
+    pub end : Position
 }
 
 ///
@@ -13,13 +60,13 @@ macro_rules! tokof {
     ($kind : ident) => (
         $crate::lexer::Lexeme{ kind: $crate::lexer::LexemeKind::$kind, .. }
     );
-    ($kind : ident ( $bind : ident )) => (
+    ($kind : ident ( $bind : pat )) => (
         $crate::lexer::Lexeme{ kind: $crate::lexer::LexemeKind::$kind($bind), .. }
     )
 }
 
 impl Span{
-    pub fn new(begin : usize, end : usize) -> Self{
+    pub fn new(begin : Position, end : Position) -> Self{
         Span{begin, end}
     }
 
@@ -27,11 +74,76 @@ impl Span{
         Span::new(self.begin, other.end)
     }
 
+    ///
+    /// The empty span directly after this one
+    ///
+    pub fn after(&self) -> Span{
+        Span::new(self.end, self.end)
+    }
+
+    ///
+    /// Render the lines of `s` covered by this span, each followed by a line of `-` under the spanned text
+    ///
+    /// The underline is laid out by display width, so it stays aligned under multi-byte
+    /// and double width characters. An empty span is still marked by a single `-`.
+    /// When `s` has several lines, each rendered line is prefixed by its line number
+    ///
     pub fn underline(&self, s : &str, message : Option<&str>) -> String{
-        let mut output = String::from(s);
-        output += "\n";
-        output += &" ".repeat(self.begin);
-        output += &"-".repeat(self.end - self.begin);
+        let begin = self.begin.offset.min(s.len());
+        let end = self.end.offset.clamp(begin, s.len());
+
+        let gutter_width = if s.contains('\n'){
+            (s[..end].matches('\n').count() + 1).to_string().len()
+        }
+        else{
+            0
+        };
+
+        let mut output = String::new();
+        let mut line_start = s[..begin].rfind('\n').map_or(0, |i| i + 1);
+        let mut line_no = s[..line_start].matches('\n').count() + 1;
+
+        loop{
+            let line_end = s[line_start..].find('\n').map_or(s.len(), |i| line_start + i);
+            let line = &s[line_start..line_end];
+
+            // The part of the span that lies on this line, the indentation of
+            // continuation lines is left out
+            let to = end.min(line_end) - line_start;
+            let from = if begin >= line_start{
+                begin - line_start
+            }
+            else{
+                (line.len() - line.trim_start().len()).min(to)
+            };
+
+            if gutter_width > 0{
+                output += &format!("{line_no:>gutter_width$} | {line}\n{:gutter_width$} | ", "");
+            }
+            else{
+                output += line;
+                output += "\n";
+            }
+
+            for c in line[..from].chars(){
+                // Keep tabs so the terminal expands them the same way in both lines
+                if c == '\t'{
+                    output.push('\t');
+                }
+                else{
+                    output += &" ".repeat(c.width().unwrap_or(0));
+                }
+            }
+            let width : usize = line[from..to].chars().map(|c| c.width().unwrap_or(0)).sum();
+            output += &"-".repeat(width.max(1));
+
+            if end <= line_end || line_end == s.len(){
+                break;
+            }
+            output += "\n";
+            line_start = line_end + 1;
+            line_no += 1;
+        }
 
         if let Some(msg) = message{
             output += " << ";
@@ -41,22 +153,191 @@ impl Span{
     }
 }
 
+impl std::fmt::Display for Position{
+    ///
+    /// Formats as `line:column`, both counted from one
+    ///
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "{}:{}", self.line + 1, self.column + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexErrorKind{
+    /// A character that cannot start any token
+    UnexpectedCharacter(char),
+    /// A number literal containing an invalid character, e.g `1.x`, `1.2.3` or `0b102`
+    MalformedNumber,
+    /// The input ended in the middle of a token, the payload names the construct
+    Unterminated(&'static str)
+}
+
+#[derive(Debug, Clone)]
+pub struct LexError{
+    pub kind : LexErrorKind,
+    pub span : Span
+}
+
+impl LexError{
+    pub fn new(kind : LexErrorKind, span : Span) -> Self{
+        Self{kind, span}
+    }
+
+    pub fn span(&self) -> &Span{
+        &self.span
+    }
+
+    pub fn code(&self) -> &'static str{
+        match self.kind{
+            LexErrorKind::UnexpectedCharacter(_) => "E101",
+            LexErrorKind::MalformedNumber => "E102",
+            LexErrorKind::Unterminated(_) => "E103"
+        }
+    }
+
+    pub fn message(&self) -> String{
+        match &self.kind{
+            LexErrorKind::UnexpectedCharacter(c) => format!("Unexpected character: {c:?}"),
+            LexErrorKind::MalformedNumber => "Malformed number".into(),
+            LexErrorKind::Unterminated(what) => format!("Unterminated {what}")
+        }
+    }
+
+    pub fn contextualize(&self, source : &str) -> String{
+        self.span().underline(source, Some(&self.message()))
+    }
+}
+
+impl std::fmt::Display for LexError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for LexError{}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum LexemeKind{
     Plus,
     Minus,
+    /// `+-` followed by a blank, or `±`, gives a number an uncertainty. `3 +-2` is the sum of 3 and -2
+    PlusMinus,
     Star,
     Slash,
+    /// `//`, integer division
+    DoubleSlash,
+    Percent,
     Karat,
     Comma,
 
+    /// An identifier, see [`is_identifier`]
     Word(String),
     Equals,
 
+    // Comparisons
+    DoubleEquals,
+    NotEquals,
+    Less,
+    LessEquals,
+    Greater,
+    GreaterEquals,
+
+    // Keywords, see [`KEYWORDS`]
+    And,
+    Or,
+    Not,
+    If,
+    True,
+    False,
+    /// `to`, conversion of a quantity to another unit
+    To,
+    /// `unit`, starts the definition of a unit
+    Unit,
+
+    Question,
+    Colon,
+
     OpenParen,
     CloseParen,
+    /// `[`, opens an interval
+    OpenBracket,
+    CloseBracket,
+
+    /// Ends a statement, unless found within parenthesis
+    Newline,
+    /// Ends a statement
+    Semicolon,
+
+    Number(NumberLiteral),
+    /// `°`, `rad` or `grad` right after a number, the unit of that angle, e.g `30°`
+    Angle(AngleMode)
+}
 
-    Number(f64)
+///
+/// The token as it is written, e.g `+`, `and` or `0xFF`. Digit separators are left out of numbers
+///
+impl std::fmt::Display for LexemeKind{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        let text = match self{
+            Self::Plus => "+",
+            Self::Minus => "-",
+            Self::PlusMinus => "+-",
+            Self::Star => "*",
+            Self::Slash => "/",
+            Self::DoubleSlash => "//",
+            Self::Percent => "%",
+            Self::Karat => "^",
+            Self::Comma => ",",
+            Self::Word(word) => word,
+            Self::Equals => "=",
+            Self::DoubleEquals => "==",
+            Self::NotEquals => "!=",
+            Self::Less => "<",
+            Self::LessEquals => "<=",
+            Self::Greater => ">",
+            Self::GreaterEquals => ">=",
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Not => "not",
+            Self::If => "if",
+            Self::True => "true",
+            Self::False => "false",
+            Self::To => "to",
+            Self::Unit => "unit",
+            Self::Question => "?",
+            Self::Colon => ":",
+            Self::OpenParen => "(",
+            Self::CloseParen => ")",
+            Self::OpenBracket => "[",
+            Self::CloseBracket => "]",
+            Self::Newline => "\n",
+            Self::Semicolon => ";",
+            Self::Number(literal) => {
+                let prefix = match literal.radix{
+                    16 => "0x",
+                    8 => "0o",
+                    2 => "0b",
+                    _ => ""
+                };
+                return write!(f, "{prefix}{}", literal.digits);
+            },
+            Self::Angle(unit) => unit.suffix()
+        };
+        write!(f, "{text}")
+    }
+}
+
+///
+/// A number literal, keeping its digits so that the exact numeric modes can read it without rounding
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumberLiteral{
+    /// The nearest float
+    pub value : f64,
+    pub radix : u32,
+    /// The digits as written, without separators nor radix prefix.
+    /// Includes the `.` and exponent of a decimal literal, e.g `.5` or `6.02e23`
+    pub digits : String
 }
 
 #[derive(Debug, Clone)]
@@ -70,48 +351,265 @@ impl Lexeme{
         Self{kind, span}
     }
 }
+///
+/// Whether `c` can begin an identifier: a letter, `_` or `°`, which begins units such as `°C`
+///
+pub fn is_identifier_start(c : char) -> bool{
+    c.is_alphabetic() || c == '_' || c == '°'
+}
+
+///
+/// Whether `c` can appear after the first character of an identifier: a letter, a digit or `_`
+///
+pub fn is_identifier_continue(c : char) -> bool{
+    c.is_alphanumeric() || c == '_'
+}
+
+///
+/// Words reserved by the language, they are lexed as their own token and cannot name anything
+///
+pub const KEYWORDS : [&str; 8] = ["and", "or", "not", "if", "true", "false", "to", "unit"];
+
+fn keyword(word : &str) -> Option<LexemeKind>{
+    match word{
+        "and" => Some(LexemeKind::And),
+        "or" => Some(LexemeKind::Or),
+        "not" => Some(LexemeKind::Not),
+        "if" => Some(LexemeKind::If),
+        "true" => Some(LexemeKind::True),
+        "false" => Some(LexemeKind::False),
+        "to" => Some(LexemeKind::To),
+        "unit" => Some(LexemeKind::Unit),
+        _ => None
+    }
+}
+
+///
+/// Whether the whole of `s` is a valid variable or function name
+///
+pub fn is_identifier(s : &str) -> bool{
+    let mut chars = s.chars();
+    chars.next().is_some_and(is_identifier_start) && chars.all(is_identifier_continue) && !KEYWORDS.contains(&s)
+}
+
 pub struct Lexer{
     input : String,
-    cursor : usize
+    cursor : Position,
+    /// Offset right after the last number, where a suffix gives the unit of its angle
+    number_end : Option<usize>
 }
 
 impl Lexer{
     pub fn new(input : String) -> Self{
         Self{
             input,
-            cursor: 0
+            cursor: Position::default(),
+            number_end: None
         }
     }
 
-    pub fn all(mut self) -> Result<Vec<Lexeme>, ()>{
+    ///
+    /// Lex the whole input
+    ///
+    /// Fails with the first lexical error encountered rather than returning the tokens before it
+    ///
+    pub fn all(mut self) -> Result<Vec<Lexeme>, LexError>{
         let mut toks = Vec::new();
 
-        while let Ok(Some(tok)) = self.next(){
+        while let Some(tok) = self.next()?{
             toks.push(tok);
         }
 
         Ok(toks)
     }
 
+    ///
+    /// The input that is left to lex
+    ///
+    fn rest(&self) -> &str{
+        &self.input[self.cursor.offset..]
+    }
+
+    ///
+    /// Move the cursor forward by `n` characters
+    ///
+    fn advance(&mut self, n : usize){
+        for c in self.input[self.cursor.offset..].chars().take(n){
+            self.cursor.advance(c);
+        }
+    }
+
+    ///
+    /// Move the cursor past all the characters matching `pred`, returns how many there were
+    ///
+    fn advance_while(&mut self, pred : impl Fn(char) -> bool) -> usize{
+        let n = self.rest().chars().take_while(|c| pred(*c)).count();
+        self.advance(n);
+        n
+    }
+
     fn next_span(&mut self, n : usize) -> Span{
         let p = self.cursor;
-        self.cursor += n;
+        self.advance(n);
         Span::new(p, self.cursor)
     }
 
-    pub fn next(&mut self) -> Result<Option<Lexeme>, ()>{
+    ///
+    /// Lex a number literal starting at the cursor
+    ///
+    /// Accepts decimals such as `12`, `1.5`, `.5` or `6.02e23`, and hexadecimal `0xFF`,
+    /// octal `0o17` or binary `0b1010` integers. Digits may be grouped with `_`, e.g `1_000_000`
+    ///
+    fn lex_number(&mut self) -> Result<Lexeme, LexError>{
+        let start = self.cursor;
+
+        let radix = match self.rest().get(..2){
+            Some("0x" | "0X") => Some((16, "hexadecimal literal")),
+            Some("0o" | "0O") => Some((8, "octal literal")),
+            Some("0b" | "0B") => Some((2, "binary literal")),
+            _ => None
+        };
+
+        let num = match radix{
+            Some((radix, name)) => {
+                self.advance(2);
+                self.lex_radix_integer(start, radix, name)?
+            },
+            None => self.lex_decimal(start)?
+        };
+
+        Ok(Lexeme::new(LexemeKind::Number(num), Span::new(start, self.cursor)))
+    }
+
+    ///
+    /// Lex the `digits[.digits][e[+-]digits]` form of a number
+    ///
+    fn lex_decimal(&mut self, start : Position) -> Result<NumberLiteral, LexError>{
+        let mut text = self.lex_digits(start, |c| c.is_ascii_digit())?;
+
+        if self.rest().starts_with('.'){
+            self.advance(1);
+
+            let frac = self.lex_digits(start, |c| c.is_ascii_digit())?;
+            if frac.is_empty(){
+                return Err(self.malformed_number(start, "decimal number"));
+            }
+            text.push('.');
+            text += &frac;
+        }
+
+        // Only read an exponent when digits follow, so that `2e` stays a number followed by a word
+        let mut exp = self.rest().chars();
+        if let Some('e' | 'E') = exp.next(){
+            let sign_len = match exp.next(){
+                Some('+' | '-') => 1,
+                _ => 0
+            };
+            if self.rest()[1 + sign_len..].starts_with(|c : char| c.is_ascii_digit()){
+                text.push('e');
+                text += &self.rest()[1..1 + sign_len];
+                self.advance(1 + sign_len);
+                text += &self.lex_digits(start, |c| c.is_ascii_digit())?;
+            }
+        }
+
+        // A second decimal point, e.g `1.2.3` or `1e5.3`
+        if self.rest().starts_with('.'){
+            return Err(self.malformed_number(start, "decimal number"));
+        }
+
+        // The text is made of digits with at most one inner dot and exponent, so it always parses
+        let value = format!("0{text}").parse().unwrap();
+        Ok(NumberLiteral{ value, radix: 10, digits: text })
+    }
+
+    ///
+    /// Lex the digits of an integer written in `radix`, the prefix is already consumed
+    ///
+    fn lex_radix_integer(&mut self, start : Position, radix : u32, name : &'static str) -> Result<NumberLiteral, LexError>{
+        let digits = self.lex_digits(start, |c| c.is_digit(radix))?;
+
+        // Digits of a larger radix or letters glued to the literal, e.g `0b102` or `0o7x`
+        if digits.is_empty() || self.rest().starts_with(|c : char| c.is_alphanumeric() || c == '.'){
+            return Err(self.malformed_number(start, name));
+        }
+
+        let value = digits.chars().fold(0.0, |acc, d| acc * radix as f64 + d.to_digit(radix).unwrap() as f64);
+        Ok(NumberLiteral{ value, radix, digits })
+    }
+
+    ///
+    /// Move past a run of digits accepted by `is_digit`, single underscores may separate them
+    ///
+    /// Returns the digits without the separators
+    ///
+    fn lex_digits(&mut self, start : Position, is_digit : impl Fn(char) -> bool) -> Result<String, LexError>{
+        let mut digits = String::new();
+
+        while let Some(c) = self.rest().chars().next(){
+            if is_digit(c){
+                digits.push(c);
+                self.advance(1);
+            }
+            else if c == '_' && !digits.is_empty(){
+                self.advance(1);
+
+                // A separator must be followed by a digit
+                if !self.rest().starts_with(&is_digit){
+                    return Err(self.malformed_number(start, "number"));
+                }
+            }
+            else{
+                break;
+            }
+        }
+
+        Ok(digits)
+    }
+
+    ///
+    /// The error for a number starting at `start` that cannot continue at the cursor
+    ///
+    /// If the input ends there the number is unterminated, otherwise the span
+    /// extends over the offending character
+    ///
+    fn malformed_number(&mut self, start : Position, what : &'static str) -> LexError{
+        let rest = self.rest();
+
+        if rest.trim().is_empty(){
+            return LexError::new(LexErrorKind::Unterminated(what), Span::new(start, self.cursor));
+        }
+
+        if !rest.starts_with(char::is_whitespace){
+            self.advance(1);
+        }
+        LexError::new(LexErrorKind::MalformedNumber, Span::new(start, self.cursor))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<Lexeme>, LexError>{
 
         // We have already consumed the whole input, return None
-        if self.cursor == self.input.len(){
+        if self.cursor.offset == self.input.len(){
             return Ok(None)
         }
 
-        let all_content = &self.input[self.cursor..];
-        let content = all_content.trim_start();
+        loop{
+            // Newlines are skipped separately, as they end statements
+            self.advance_while(|c| c.is_whitespace() && c != '\n');
 
-        let ws_count = all_content.len() - content.len();
+            if !self.rest().starts_with('#'){
+                break;
+            }
+            // A line comment, runs up to the end of the line
+            self.advance_while(|c| c != '\n');
+        }
+        let content = self.rest();
 
-        self.cursor += ws_count;
+        // Only trailing whitespace was left
+        if content.is_empty(){
+            return Ok(None)
+        }
 
         if content.starts_with('('){
             Ok(Some(Lexeme::new(LexemeKind::OpenParen, self.next_span(1))))
@@ -119,9 +617,22 @@ impl Lexer{
         else if content.starts_with(')'){
             Ok(Some(Lexeme::new(LexemeKind::CloseParen, self.next_span(1))))
         }
+        else if content.starts_with('['){
+            Ok(Some(Lexeme::new(LexemeKind::OpenBracket, self.next_span(1))))
+        }
+        else if content.starts_with(']'){
+            Ok(Some(Lexeme::new(LexemeKind::CloseBracket, self.next_span(1))))
+        }
         else if content.starts_with('*'){
             Ok(Some(Lexeme::new(LexemeKind::Star, self.next_span(1))))
         }
+        // Only when spaced, `3 +-2` is the sum of 3 and -2
+        else if content.starts_with("+-") && content[2..].starts_with(char::is_whitespace){
+            Ok(Some(Lexeme::new(LexemeKind::PlusMinus, self.next_span(2))))
+        }
+        else if content.starts_with('±'){
+            Ok(Some(Lexeme::new(LexemeKind::PlusMinus, self.next_span(1))))
+        }
         else if content.starts_with('+'){
             Ok(Some(Lexeme::new(LexemeKind::Plus, self.next_span(1))))
         }
@@ -131,76 +642,80 @@ impl Lexer{
         else if content.starts_with('^'){
             Ok(Some(Lexeme::new(LexemeKind::Karat, self.next_span(1))))
         }
+        else if content.starts_with("//"){
+            Ok(Some(Lexeme::new(LexemeKind::DoubleSlash, self.next_span(2))))
+        }
         else if content.starts_with('/'){
             Ok(Some(Lexeme::new(LexemeKind::Slash, self.next_span(1))))
         }
+        else if content.starts_with('%'){
+            Ok(Some(Lexeme::new(LexemeKind::Percent, self.next_span(1))))
+        }
         else if content.starts_with(','){
             Ok(Some(Lexeme::new(LexemeKind::Comma, self.next_span(1))))
         }
+        else if content.starts_with("=="){
+            Ok(Some(Lexeme::new(LexemeKind::DoubleEquals, self.next_span(2))))
+        }
         else if content.starts_with('='){
             Ok(Some(Lexeme::new(LexemeKind::Equals, self.next_span(1))))
         }
+        else if content.starts_with("!="){
+            Ok(Some(Lexeme::new(LexemeKind::NotEquals, self.next_span(2))))
+        }
+        else if content.starts_with("<="){
+            Ok(Some(Lexeme::new(LexemeKind::LessEquals, self.next_span(2))))
+        }
+        else if content.starts_with('<'){
+            Ok(Some(Lexeme::new(LexemeKind::Less, self.next_span(1))))
+        }
+        else if content.starts_with(">="){
+            Ok(Some(Lexeme::new(LexemeKind::GreaterEquals, self.next_span(2))))
+        }
+        else if content.starts_with('>'){
+            Ok(Some(Lexeme::new(LexemeKind::Greater, self.next_span(1))))
+        }
+        else if content.starts_with('?'){
+            Ok(Some(Lexeme::new(LexemeKind::Question, self.next_span(1))))
+        }
+        else if content.starts_with(':'){
+            Ok(Some(Lexeme::new(LexemeKind::Colon, self.next_span(1))))
+        }
+        else if content.starts_with('\n'){
+            Ok(Some(Lexeme::new(LexemeKind::Newline, self.next_span(1))))
+        }
+        else if content.starts_with(';'){
+            Ok(Some(Lexeme::new(LexemeKind::Semicolon, self.next_span(1))))
+        }
         else{
             // Try to recognize a literal number or word 
-            if content.starts_with(|c : char| c.is_ascii_digit()){
-                // Parse a digit
-
-                let mut has_enc_dot = false;
-                let start = self.cursor;
-                for el in content.chars(){
-
-                    // We have just encountered a dot
-                    let needs_digit = has_enc_dot && &self.input[self.cursor..=self.cursor] == ".";
-
-                    match el{
-                        '.' => {
-                            if has_enc_dot{ 
-                                break
-                            }
-                            has_enc_dot = true;
-                            self.cursor+=1;
-                        },
-                        '0'..='9' => {
-                            self.cursor+=1;
-                        },
-                        _ => {
-                            if needs_digit{
-                                return Err(())
-                            }
-                            else{
-                                break;
-                            }
-                        },
-                    }
-
-                }
-
-                let num = &self.input[start..self.cursor];
-                let num : f64 = num.parse().unwrap();
-              
-                Ok(Some(Lexeme::new(LexemeKind::Number(num), Span::new(start, self.cursor))))
-
+            let leading_dot = content.starts_with('.') && content[1..].starts_with(|c : char| c.is_ascii_digit());
+            if leading_dot || content.starts_with(|c : char| c.is_ascii_digit()){
+                let number = self.lex_number()?;
+                self.number_end = Some(self.cursor.offset);
+                Ok(Some(number))
             }
-            else if content.starts_with(|c : char| c.is_alphabetic()){
+            else if content.starts_with(is_identifier_start){
                 // We read in a word
                 let start = self.cursor;
 
-                for el in content.chars(){
-                    if !el.is_alphabetic(){
-                        break;
-                    }
-                    self.cursor+=1;
-                }
+                // The first character may be one that cannot continue an identifier, e.g the `°` of `°C`
+                self.advance(1);
+                self.advance_while(is_identifier_continue);
 
-                let end = self.cursor;
+                let kw = &self.input[start.offset..self.cursor.offset];
+                let suffix = AngleMode::from_suffix(kw).filter(|_| self.number_end == Some(start.offset));
+                let kind = match suffix{
+                    Some(unit) => LexemeKind::Angle(unit),
+                    None => keyword(kw).unwrap_or_else(|| LexemeKind::Word(kw.to_string()))
+                };
 
-                let kw = &self.input[start..end];
-                
-                Ok(Some(Lexeme::new(LexemeKind::Word(kw.to_string()), Span::new(start, self.cursor))))
+                Ok(Some(Lexeme::new(kind, Span::new(start, self.cursor))))
             }
             else{
                 // Unrecognized input
-                Err(())
+                let c = content.chars().next().unwrap();
+                Err(LexError::new(LexErrorKind::UnexpectedCharacter(c), self.next_span(1)))
             }
         }
 
@@ -0,0 +1,20 @@
+pub mod lexer;
+pub mod statement;
+pub mod program;
+pub mod expression;
+pub mod evaluator;
+pub mod value;
+pub mod units;
+pub mod interval;
+pub mod measurement;
+pub mod angle;
+pub mod format;
+mod decimal;
+pub mod functions;
+mod builtins;
+pub mod error;
+pub mod calculator;
+
+pub use calculator::Calculator;
+pub use error::CalcError;
+pub use value::Value;
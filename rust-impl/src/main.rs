@@ -1,44 +1,48 @@
-pub mod lexer;
-pub mod statement;
-pub mod expression;
-pub mod evaluator;
-
+use ise_rust_calc_lib::Calculator;
 
 fn main() {
 
-    let input1 = "foo = INF / 20";
-    let input2 = "bar = foz * 4 - 31";
-    let input3 = "baz = bar / 3";
-    let input4 = "baz ^ 2";
-
-    let t1 = lexer::Lexer::new(input1.to_string()).all().unwrap();
-    let t2 = lexer::Lexer::new(input2.to_string()).all().unwrap();
-    let t3 = lexer::Lexer::new(input3.to_string()).all().unwrap();
-    let t4 = lexer::Lexer::new(input4.to_string()).all().unwrap();
-
-
-    let s1 = statement::Statement::parse(&t1).unwrap();
-    let s2 = statement::Statement::parse(&t2).unwrap();
-    let s3 = statement::Statement::parse(&t3).unwrap();
-    let s4 = statement::Statement::parse(&t4).unwrap();
-    
-
-    let mut eval = evaluator::Evaluator::new();
-
-    if let Err(e) = eval.eval_statement(&s1){
-        println!("{}", e.contextualize(input1));
+    // Run the `.calc` file given as argument, if any
+    if let Some(path) = std::env::args().nth(1){
+        let source = match std::fs::read_to_string(&path){
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("{path}: {e}");
+                std::process::exit(1);
+            }
+        };
+
+        let mut calc = Calculator::new();
+        match calc.run(&source){
+            Ok(values) => {
+                for val in values{
+                    println!("{}", calc.evaluator().display(&val));
+                }
+            },
+            Err(e) => {
+                eprintln!("{}", e.contextualize(&source));
+                std::process::exit(1);
+            }
+        }
         return;
     }
-    if let Err(e) = eval.eval_statement(&s2){
-        println!("{}", e.contextualize(input2));
-        return;
-    }
-    if let Err(e) = eval.eval_statement(&s3){
-        println!("{}", e.contextualize(input3));
-        return;
-    }
-    if let Err(e) = eval.eval_statement(&s4){
-        println!("{}", e.contextualize(input4));
-        return;
+
+    let inputs = [
+        "foo = INF / 20",
+        "bar = foz * 4 - 31",
+        "baz = bar / 3",
+        "baz ^ 2",
+    ];
+
+    let mut calc = Calculator::new();
+
+    for input in inputs{
+        match calc.eval(input){
+            Ok(val) => println!("{input} => {}", calc.evaluator().display(&val)),
+            Err(e) => {
+                println!("{}", e.contextualize(input));
+                return;
+            }
+        }
     }
 }
@@ -0,0 +1,134 @@
+//!
+//! Numbers with a standard uncertainty, see [`Measurement`]
+//!
+//! `9.81 +- 0.02` is a measurement of an independent source of error. Operators and functions propagate
+//! uncertainties to first order: each result keeps how much it moves when each source moves by its
+//! uncertainty, so that correlated operands are accounted for, e.g `x - x` is exactly 0
+//!
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::evaluator::NumericMode;
+use crate::expression::BinOp;
+use crate::value::{OperationError, Value};
+
+/// Identifier of the next independent source of error
+static NEXT_SOURCE : AtomicU64 = AtomicU64::new(0);
+
+///
+/// A value known up to a standard uncertainty, e.g `9.81 ± 0.02`
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement{
+    value : f64,
+    /// The change of the value for a change of one uncertainty of each source of error it depends on
+    deviations : BTreeMap<u64, f64>
+}
+
+impl Measurement{
+    ///
+    /// `value` measured with the uncertainty `sigma`, independent of every other measurement
+    ///
+    pub fn new(value : f64, sigma : f64) -> Self{
+        Self::exact(value).with_source(sigma)
+    }
+
+    ///
+    /// `value` without uncertainty
+    ///
+    pub fn exact(value : f64) -> Self{
+        Self{ value, deviations: BTreeMap::new() }
+    }
+
+    pub fn value(&self) -> f64{
+        self.value
+    }
+
+    ///
+    /// The standard uncertainty, from the deviations of all the sources the value depends on
+    ///
+    pub fn sigma(&self) -> f64{
+        self.deviations.values().fold(0.0, |sigma, d| sigma.hypot(*d))
+    }
+
+    ///
+    /// `self` with a further independent source of error of uncertainty `sigma`
+    ///
+    pub(crate) fn with_source(mut self, sigma : f64) -> Self{
+        self.deviations.insert(NEXT_SOURCE.fetch_add(1, Ordering::Relaxed), sigma);
+        self
+    }
+
+    ///
+    /// The measurement of `value`, a function of `inputs` with the derivatives `partials` with respect to each of them
+    ///
+    pub(crate) fn propagate(value : f64, inputs : &[Measurement], partials : &[f64]) -> Self{
+        let mut deviations = BTreeMap::new();
+        for (input, partial) in inputs.iter().zip(partials){
+            for (source, deviation) in &input.deviations{
+                let change = if *partial == 0.0 { 0.0 } else { partial * deviation };
+                *deviations.entry(*source).or_insert(0.0) += change;
+            }
+        }
+        Self{ value, deviations }
+    }
+
+    pub(crate) fn negate(&self) -> Self{
+        Self::propagate(-self.value, std::slice::from_ref(self), &[-1.0])
+    }
+}
+
+///
+/// `value +- sigma`, `sigma` must be a number without uncertainty that is not negative.
+/// A value that already is a measurement gets a further independent source of error
+///
+/// Intervals are not given uncertainties, their bounds already are what is known of them
+///
+pub(crate) fn plus_minus(value : &Value, sigma : &Value) -> Result<Value, OperationError>{
+    if matches!(value, Value::Interval(_)) || matches!(sigma, Value::Interval(_)){
+        return Err(OperationError::IntervalUncertainty);
+    }
+    let measurement = value.as_measurement().ok_or(OperationError::TypeMismatch)?;
+    let sigma = sigma.as_number().ok_or(OperationError::TypeMismatch)?;
+    if sigma.is_nan() || sigma < 0.0{
+        return Err(OperationError::NegativeUncertainty);
+    }
+    Ok(Value::Measurement(measurement.with_source(sigma)))
+}
+
+///
+/// Apply one of the arithmetic operators `+ - * / // % ^` to measurements
+///
+/// The value is that of the operator on floats, and so are its errors
+///
+pub(crate) fn arithmetic(op : BinOp, x : Measurement, y : Measurement) -> Result<Measurement, OperationError>{
+    let (a, b) = (x.value, y.value);
+    let value = Value::arithmetic(op, &Value::Number(a), &Value::Number(b), NumericMode::Float)?
+        .as_number()
+        .expect("operators on floats give floats");
+
+    let partials = match op{
+        BinOp::Add => [1.0, 1.0],
+        BinOp::Sub => [1.0, -1.0],
+        BinOp::Mul => [b, a],
+        BinOp::Div => [1.0 / b, -a / (b * b)],
+        // Constant between the steps of the quotient
+        BinOp::IntDiv => [0.0, 0.0],
+        BinOp::Mod => [1.0, -(a / b).floor()],
+        BinOp::Exp => {
+            // An exact exponent does not depend on the logarithm of the base, which negative bases lack
+            let by_exponent = if y.deviations.is_empty() { 0.0 } else { value * a.ln() };
+            let by_base = if b == 0.0 { 0.0 } else { b * a.powf(b - 1.0) };
+            [by_base, by_exponent]
+        },
+        _ => unreachable!("{:?} is not an arithmetic operator", op.symbol())
+    };
+    Ok(Measurement::propagate(value, &[x, y], &partials))
+}
+
+impl std::fmt::Display for Measurement{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "{} ± {}", self.value, self.sigma())
+    }
+}
@@ -0,0 +1,57 @@
+use crate::expression::ParseOptions;
+use crate::lexer::{Lexeme, LexemeKind};
+use crate::statement::{ParserError, Statement};
+
+///
+/// A sequence of statements, such as the content of a `.calc` file
+///
+/// Statements are separated by newlines or `;`, blank statements are skipped.
+/// A newline within parenthesis does not end the statement, so long expressions
+/// can be spread over several lines
+///
+#[derive(Debug)]
+pub struct Program{
+    pub statements : Vec<Statement>
+}
+
+impl Program{
+    pub fn into_str(&self) -> String{
+        self.statements.iter()
+            .map(Statement::into_str)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn parse(toks : &[Lexeme]) -> Result<Self, ParserError>{
+        Program::parse_with(toks, &ParseOptions::default())
+    }
+
+    pub fn parse_with(toks : &[Lexeme], options : &ParseOptions) -> Result<Self, ParserError>{
+        let mut statements = Vec::new();
+        let mut current = Vec::new();
+        let mut ind : usize = 0;
+
+        for tok in toks{
+            match tok.kind{
+                LexemeKind::OpenParen | LexemeKind::OpenBracket => ind += 1,
+                LexemeKind::CloseParen | LexemeKind::CloseBracket => ind = ind.saturating_sub(1),
+                LexemeKind::Newline if ind > 0 => continue,
+                LexemeKind::Newline | LexemeKind::Semicolon if ind == 0 => {
+                    if !current.is_empty(){
+                        statements.push(Statement::parse_with(&current, options)?);
+                        current.clear();
+                    }
+                    continue;
+                },
+                _ => ()
+            }
+            current.push(tok.clone());
+        }
+
+        if !current.is_empty(){
+            statements.push(Statement::parse_with(&current, options)?);
+        }
+
+        Ok(Program{statements})
+    }
+}
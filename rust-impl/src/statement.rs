@@ -1,5 +1,5 @@
-use crate::expression::{ArithmeticNode, ParserError as ArithmeticParserError};
-use crate::lexer::Lexeme;
+use crate::expression::{ArithmeticNode, ParseOptions, ParserError as ArithmeticParserError};
+use crate::lexer::{Lexeme, LexemeKind, Span};
 use crate::tokof;
 
 #[derive(Debug)]
@@ -7,6 +7,20 @@ pub enum Statement{
     Expr(ArithmeticNode),
     Assignment{
         var_name : String,
+        name_span : Span,
+        expr : ArithmeticNode
+    },
+    /// `name(params) = body`, the body is only evaluated when the function is called
+    FunctionDef{
+        name : String,
+        name_span : Span,
+        params : Vec<String>,
+        body : ArithmeticNode
+    },
+    /// `unit name = expr`, the value of `expr` is the size of the unit
+    UnitDef{
+        name : String,
+        name_span : Span,
         expr : ArithmeticNode
     }
 }
@@ -14,7 +28,59 @@ pub enum Statement{
 
 #[derive(Debug)]
 pub enum ParserError{
-    Arithmetic(ArithmeticParserError)
+    Arithmetic(ArithmeticParserError),
+    /// A function definition names the same parameter twice
+    DuplicateParameter(String, Span),
+    /// A variable, function or parameter is named like the symbol of a unit, e.g `°x = 1`
+    UnitSymbolName(String, Span),
+    /// The value of a definition was asked for, definitions have none, see [`crate::Calculator::execute`]
+    Definition(Span)
+}
+
+impl ParserError{
+    pub fn span(&self) -> &Span{
+        match self{
+            Self::Arithmetic(e) => e.span(),
+            Self::DuplicateParameter(_, span) | Self::UnitSymbolName(_, span) | Self::Definition(span) => span
+        }
+    }
+
+    pub fn code(&self) -> &'static str{
+        match self{
+            Self::Arithmetic(e) => e.code(),
+            Self::DuplicateParameter(..) => "E208",
+            Self::UnitSymbolName(..) => "E212",
+            Self::Definition(_) => "E213"
+        }
+    }
+
+    pub fn message(&self) -> String{
+        match self{
+            Self::Arithmetic(e) => e.message(),
+            Self::DuplicateParameter(name, _) => format!("Duplicate parameter: {name:?}"),
+            Self::UnitSymbolName(name, _) => format!("Invalid name: {name:?}, only units are named with '°'"),
+            Self::Definition(_) => "Expected a value, a definition has none".to_string()
+        }
+    }
+
+    pub fn contextualize(&self, source : &str) -> String{
+        self.span().underline(source, Some(&self.message()))
+    }
+}
+
+impl std::fmt::Display for ParserError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ParserError{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>{
+        match self{
+            Self::Arithmetic(e) => Some(e),
+            Self::DuplicateParameter(..) | Self::UnitSymbolName(..) | Self::Definition(_) => None
+        }
+    }
 }
 
 
@@ -24,32 +90,125 @@ impl Statement{
     pub fn into_str(&self) -> String{
         match self{
             Self::Expr(e) => e.into_rpn(),
-            Self::Assignment { var_name, expr } => format!("{} <- {}", var_name, expr.into_rpn())
+            Self::Assignment { var_name, expr, .. } => format!("{} <- {}", var_name, expr.into_rpn()),
+            Self::FunctionDef { name, params, body, .. } => format!("{}({}) <- {}", name, params.join(", "), body.into_rpn()),
+            Self::UnitDef { name, expr, .. } => format!("unit {} <- {}", name, expr.into_rpn())
+        }
+    }
+
+    pub fn span(&self) -> Span{
+        match self{
+            Self::Expr(e) => e.span.clone(),
+            Self::Assignment { name_span, expr, .. } => name_span.up_to(&expr.span),
+            Self::FunctionDef { name_span, body, .. } => name_span.up_to(&body.span),
+            Self::UnitDef { name_span, expr, .. } => name_span.up_to(&expr.span)
         }
     }
 
 
     pub fn parse(stmnt : &[Lexeme]) -> Result<Self, ParserError>{
+        Statement::parse_with(stmnt, &ParseOptions::default())
+    }
+
+    pub fn parse_with(stmnt : &[Lexeme], options : &ParseOptions) -> Result<Self, ParserError>{
+        if let Some(eq_index) = Self::function_header(stmnt){
+            return Self::parse_function(stmnt, eq_index, options);
+        }
+
         match stmnt{
             // Assignment 
-            [tokof!(Word(var_name)), tokof!(Equals), ..] => {
+            [name @ tokof!(Word(var_name)), eq @ tokof!(Equals), ..] => {
                 // Assigning expression to var_name 
+                not_unit_symbol(var_name, &name.span)?;
 
                 let expr = &stmnt[2..];
 
-                let expression = ArithmeticNode::parse(expr).map_err(ParserError::Arithmetic)?;
+                let expression = ArithmeticNode::parse_with(expr, &eq.span, options).map_err(ParserError::Arithmetic)?;
 
                 Ok(Statement::Assignment{
                     var_name: var_name.to_string(),
+                    name_span: name.span.clone(),
                     expr : expression
                 })
             },
 
+            // Unit definition
+            [tokof!(Unit), name @ tokof!(Word(unit_name)), eq @ tokof!(Equals), ..] => {
+                let expression = ArithmeticNode::parse_with(&stmnt[3..], &eq.span, options).map_err(ParserError::Arithmetic)?;
+
+                Ok(Statement::UnitDef{
+                    name: unit_name.to_string(),
+                    name_span: name.span.clone(),
+                    expr: expression
+                })
+            },
+
             // Pure expression
             _ => {
-                let expression = ArithmeticNode::parse(stmnt).map_err(ParserError::Arithmetic)?;
+                let expression = ArithmeticNode::parse_with(stmnt, &Span::default(), options).map_err(ParserError::Arithmetic)?;
                 Ok(Statement::Expr(expression))
             }
         }
     }
+
+    ///
+    /// Index of the `=` if the statement starts with `name(...) =`
+    ///
+    /// Parameters cannot contain parenthesis, so the first `)` ends the header
+    ///
+    fn function_header(stmnt : &[Lexeme]) -> Option<usize>{
+        let [tokof!(Word(_)), tokof!(OpenParen), rest @ ..] = stmnt else{
+            return None;
+        };
+        let close = rest.iter().position(|t| t.kind == LexemeKind::CloseParen)?;
+
+        match rest.get(close + 1){
+            Some(tokof!(Equals)) => Some(close + 3),
+            _ => None
+        }
+    }
+
+    fn parse_function(stmnt : &[Lexeme], eq_index : usize, options : &ParseOptions) -> Result<Self, ParserError>{
+        let tokof!(Word(name)) = &stmnt[0] else{
+            unreachable!("function_header checked the name");
+        };
+        not_unit_symbol(name, &stmnt[0].span)?;
+
+        // Between the parenthesis, identifiers separated by commas
+        let mut params : Vec<String> = Vec::new();
+        let param_toks = &stmnt[2..eq_index - 1];
+        for (i, tok) in param_toks.iter().enumerate(){
+            match (&tok.kind, i % 2 == 0){
+                (LexemeKind::Word(param), true) => {
+                    not_unit_symbol(param, &tok.span)?;
+                    if params.contains(param){
+                        return Err(ParserError::DuplicateParameter(param.clone(), tok.span.clone()));
+                    }
+                    params.push(param.clone());
+                },
+                (LexemeKind::Comma, false) if i + 1 < param_toks.len() => (),
+                _ => return Err(ParserError::Arithmetic(ArithmeticParserError::UnexpectedToken(tok.clone())))
+            }
+        }
+
+        let eq = &stmnt[eq_index];
+        let body = ArithmeticNode::parse_with(&stmnt[eq_index + 1..], &eq.span, options).map_err(ParserError::Arithmetic)?;
+
+        Ok(Statement::FunctionDef{
+            name: name.to_string(),
+            name_span: stmnt[0].span.clone(),
+            params,
+            body
+        })
+    }
+}
+
+///
+/// Fail for a name starting with `°`, those are left to the symbols of units such as `°C`
+///
+fn not_unit_symbol(name : &str, span : &Span) -> Result<(), ParserError>{
+    if name.starts_with('°'){
+        return Err(ParserError::UnitSymbolName(name.to_string(), span.clone()));
+    }
+    Ok(())
 }
@@ -0,0 +1,642 @@
+//!
+//! Physical units attached to values, see [`Quantity`]
+//!
+//! A name that is not a variable is looked up in the [`UnitRegistry`] of the evaluator, so `5 m / 2 s`
+//! is the quantity `2.5 m/s`. Quantities keep the units they were written with, operators check that
+//! the dimensions of their operands agree and convert the right operand to the units of the left one
+//!
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::decimal;
+use crate::evaluator::NumericMode;
+use crate::expression::BinOp;
+use crate::interval::{self, Interval};
+use crate::measurement::{self, Measurement};
+use crate::lexer::is_identifier;
+use crate::value::{OperationError, Value, MAX_EXACT_POW_BITS};
+
+/// Names of common dimensions, by their exponents of length, mass, time, current, temperature, amount and luminosity
+const DIMENSION_NAMES : [([i32; 7], &str); 19] = [
+    ([1, 0, 0, 0, 0, 0, 0], "length"),
+    ([0, 1, 0, 0, 0, 0, 0], "mass"),
+    ([0, 0, 1, 0, 0, 0, 0], "time"),
+    ([0, 0, 0, 1, 0, 0, 0], "current"),
+    ([0, 0, 0, 0, 1, 0, 0], "temperature"),
+    ([0, 0, 0, 0, 0, 1, 0], "amount of substance"),
+    ([0, 0, 0, 0, 0, 0, 1], "luminous intensity"),
+    ([2, 0, 0, 0, 0, 0, 0], "area"),
+    ([3, 0, 0, 0, 0, 0, 0], "volume"),
+    ([1, 0, -1, 0, 0, 0, 0], "speed"),
+    ([1, 0, -2, 0, 0, 0, 0], "acceleration"),
+    ([0, 0, -1, 0, 0, 0, 0], "frequency"),
+    ([1, 1, -2, 0, 0, 0, 0], "force"),
+    ([2, 1, -2, 0, 0, 0, 0], "energy"),
+    ([2, 1, -3, 0, 0, 0, 0], "power"),
+    ([-1, 1, -2, 0, 0, 0, 0], "pressure"),
+    ([0, 0, 1, 1, 0, 0, 0], "charge"),
+    ([2, 1, -3, -1, 0, 0, 0], "voltage"),
+    ([2, 1, -3, -2, 0, 0, 0], "resistance")
+];
+
+/// SI prefixes and their power of ten, `da` comes before `d` so that `dam` is a decametre
+const PREFIXES : [(&str, i32); 25] = [
+    ("Q", 30), ("R", 27), ("Y", 24), ("Z", 21), ("E", 18), ("P", 15), ("T", 12), ("G", 9), ("M", 6), ("k", 3), ("h", 2), ("da", 1),
+    ("d", -1), ("c", -2), ("m", -3), ("µ", -6), ("u", -6), ("n", -9), ("p", -12), ("f", -15), ("a", -18), ("z", -21), ("y", -24),
+    ("r", -27), ("q", -30)
+];
+
+///
+/// Exponents of the seven SI base dimensions: length, mass, time, current, temperature,
+/// amount of substance and luminous intensity
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct Dimension(pub [i32; 7]);
+
+impl Dimension{
+    pub const NONE : Self = Self([0; 7]);
+    pub const LENGTH : Self = Self([1, 0, 0, 0, 0, 0, 0]);
+    pub const MASS : Self = Self([0, 1, 0, 0, 0, 0, 0]);
+    pub const TIME : Self = Self([0, 0, 1, 0, 0, 0, 0]);
+    pub const CURRENT : Self = Self([0, 0, 0, 1, 0, 0, 0]);
+    pub const TEMPERATURE : Self = Self([0, 0, 0, 0, 1, 0, 0]);
+    pub const AMOUNT : Self = Self([0, 0, 0, 0, 0, 1, 0]);
+    pub const LUMINOSITY : Self = Self([0, 0, 0, 0, 0, 0, 1]);
+
+    pub fn is_dimensionless(&self) -> bool{
+        *self == Self::NONE
+    }
+
+    ///
+    /// The dimension of the product of a quantity of this dimension with a quantity of `other` raised to `exponent`
+    ///
+    pub fn times(&self, other : &Dimension, exponent : i32) -> Dimension{
+        let mut exponents = self.0;
+        for (e, o) in exponents.iter_mut().zip(other.0){
+            *e = e.saturating_add(o.saturating_mul(exponent));
+        }
+        Self(exponents)
+    }
+
+    ///
+    /// Name of the physical quantity of this dimension, if it is a common one
+    ///
+    pub fn name(&self) -> Option<&'static str>{
+        DIMENSION_NAMES.iter().find(|(exponents, _)| *exponents == self.0).map(|(_, name)| *name)
+    }
+}
+
+///
+/// A unit that has a name, e.g `ft` or `km`
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedUnit{
+    pub symbol : String,
+    /// Size of the unit in SI base units, e.g 0.3048 for `ft`
+    pub scale : BigRational,
+    /// Value in SI base units of the zero of an affine unit, e.g 273.15 for `°C`, zero for other units
+    pub offset : BigRational,
+    pub dimension : Dimension
+}
+
+impl NamedUnit{
+    ///
+    /// A unit `scale` times the size of the SI unit of `dimension`
+    ///
+    pub fn new(symbol : &str, scale : BigRational, dimension : Dimension) -> Self{
+        Self{ symbol: symbol.to_string(), scale, offset: BigRational::zero(), dimension }
+    }
+}
+
+///
+/// A product of powers of named units, e.g `km/h`. The empty product is the unit of plain numbers
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Unit{
+    factors : Vec<(NamedUnit, i32)>
+}
+
+impl From<NamedUnit> for Unit{
+    fn from(unit : NamedUnit) -> Self{
+        Self{ factors: vec![(unit, 1)] }
+    }
+}
+
+impl Unit{
+    ///
+    /// The named units and their exponents, in the order they were written
+    ///
+    pub fn factors(&self) -> &[(NamedUnit, i32)]{
+        &self.factors
+    }
+
+    pub fn dimension(&self) -> Dimension{
+        self.factors.iter().fold(Dimension::NONE, |d, (unit, e)| d.times(&unit.dimension, *e))
+    }
+
+    ///
+    /// Size of the unit in SI base units
+    ///
+    pub fn scale(&self) -> BigRational{
+        self.factors.iter().map(|(unit, e)| unit.scale.pow(*e)).product()
+    }
+
+    ///
+    /// Value in SI base units of the zero of the unit. Only an affine unit on its own has an offset,
+    /// within a product like `°C/s` it stands for a difference of temperatures
+    ///
+    pub fn offset(&self) -> BigRational{
+        match &self.factors[..]{
+            [(unit, 1)] => unit.offset.clone(),
+            _ => BigRational::zero()
+        }
+    }
+
+    ///
+    /// This unit times `other` raised to `exponent`, the exponents of a same named unit are added up
+    ///
+    fn times(&self, other : &Unit, exponent : i32) -> Option<Unit>{
+        let mut factors = self.factors.clone();
+        for (unit, e) in &other.factors{
+            let e = e.checked_mul(exponent)?;
+            match factors.iter_mut().find(|(u, _)| u == unit){
+                Some((_, existing)) => *existing = existing.checked_add(e)?,
+                None => factors.push((unit.clone(), e))
+            }
+        }
+        factors.retain(|(_, e)| *e != 0);
+        Self{ factors }.bounded()
+    }
+
+    ///
+    /// This unit raised to `exponent`, or `None` if an exponent would not be an integer or be too large
+    ///
+    fn pow(&self, exponent : &BigRational) -> Option<Unit>{
+        let factors = self.factors.iter()
+            .map(|(unit, e)| {
+                let e = exponent * BigRational::from_integer((*e).into());
+                Some((unit.clone(), e.is_integer().then(|| e.to_integer().to_i32())??))
+            })
+            .filter(|f| !matches!(f, Some((_, 0))))
+            .collect::<Option<_>>()?;
+        Self{ factors }.bounded()
+    }
+
+    ///
+    /// The unit, or `None` if its scale would take more than [`MAX_EXACT_POW_BITS`] to write out like a too large exact power
+    ///
+    fn bounded(self) -> Option<Unit>{
+        let fits = self.factors.iter().all(|(unit, e)| {
+            let bits = unit.scale.numer().bits().max(unit.scale.denom().bits());
+            bits.saturating_mul(u64::from(e.unsigned_abs())) <= MAX_EXACT_POW_BITS
+        });
+        fits.then_some(self)
+    }
+}
+
+impl std::fmt::Display for Unit{
+    ///
+    /// Units with a positive exponent are joined by `*`, each of the others follows a `/`, e.g `kg*m/s^2`.
+    /// Without any positive exponent the negative ones are written as such, e.g `s^-1`
+    ///
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        let power = |f : &mut std::fmt::Formatter<'_>, unit : &NamedUnit, e : i32| {
+            if e == 1 { write!(f, "{}", unit.symbol) } else { write!(f, "{}^{e}", unit.symbol) }
+        };
+
+        if self.factors.iter().all(|(_, e)| *e < 0){
+            for (i, (unit, e)) in self.factors.iter().enumerate(){
+                if i > 0{
+                    write!(f, "*")?;
+                }
+                power(f, unit, *e)?;
+            }
+            return Ok(());
+        }
+
+        let mut first = true;
+        for (unit, e) in self.factors.iter().filter(|(_, e)| *e > 0){
+            if !first{
+                write!(f, "*")?;
+            }
+            first = false;
+            power(f, unit, *e)?;
+        }
+        for (unit, e) in self.factors.iter().filter(|(_, e)| *e < 0){
+            write!(f, "/")?;
+            power(f, unit, -e)?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// A number with a unit, e.g `72 km/h`
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity{
+    /// A number of any numeric kind, never a quantity itself
+    pub magnitude : Value,
+    pub unit : Unit
+}
+
+impl std::fmt::Display for Quantity{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "{}", with_unit(&self.magnitude.to_string(), &self.unit))
+    }
+}
+
+///
+/// A written magnitude followed by `unit`
+///
+pub(crate) fn with_unit(magnitude : &str, unit : &Unit) -> String{
+    // Fractions, complex numbers and measurements would read as part of an expression, e.g `1/3 m`
+    if !magnitude.starts_with('[') && magnitude.trim_start_matches('-').contains(['/', '+', '-', '±']){
+        format!("({magnitude}) {unit}")
+    }
+    else{
+        format!("{magnitude} {unit}")
+    }
+}
+
+///
+/// The name given to [`UnitRegistry::define`] is not an identifier, so it could never be used
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidUnitName(pub String);
+
+impl std::fmt::Display for InvalidUnitName{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        write!(f, "Invalid unit name: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidUnitName{}
+
+#[derive(Debug, Clone)]
+struct Definition{
+    unit : NamedUnit,
+    /// Whether the unit takes SI prefixes, e.g `km` for `m`
+    prefixable : bool
+}
+
+///
+/// The units known to an [`Evaluator`](crate::evaluator::Evaluator), by symbol
+///
+#[derive(Debug, Default)]
+pub struct UnitRegistry{
+    units : HashMap<String, Definition>
+}
+
+impl UnitRegistry{
+    ///
+    /// A registry without any unit
+    ///
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    ///
+    /// A registry holding the SI units, with their prefixes, and common units of other systems
+    ///
+    pub fn with_builtins() -> Self{
+        let mut registry = Self::new();
+        register_builtins(&mut registry);
+        registry
+    }
+
+    ///
+    /// Add a unit, replacing any previous one of the same symbol. Prefixes do not apply to it
+    ///
+    pub fn define(&mut self, unit : NamedUnit) -> Result<(), InvalidUnitName>{
+        self.insert(unit, false)
+    }
+
+    fn insert(&mut self, unit : NamedUnit, prefixable : bool) -> Result<(), InvalidUnitName>{
+        if !is_identifier(&unit.symbol){
+            return Err(InvalidUnitName(unit.symbol));
+        }
+        self.units.insert(unit.symbol.clone(), Definition{ unit, prefixable });
+        Ok(())
+    }
+
+    pub fn remove(&mut self, symbol : &str) -> Option<NamedUnit>{
+        self.units.remove(symbol).map(|d| d.unit)
+    }
+
+    ///
+    /// The unit of `symbol`, which may be a prefixed unit such as `km`
+    ///
+    /// A unit defined with the full symbol takes precedence, so `min` is a minute rather than a milli-inch
+    ///
+    pub fn get(&self, symbol : &str) -> Option<NamedUnit>{
+        if let Some(definition) = self.units.get(symbol){
+            return Some(definition.unit.clone());
+        }
+
+        PREFIXES.iter().find_map(|(prefix, power)| {
+            let definition = self.units.get(symbol.strip_prefix(prefix)?).filter(|d| d.prefixable)?;
+            let factor = BigRational::from_integer(10.into()).pow(*power);
+            Some(NamedUnit{
+                symbol: symbol.to_string(),
+                scale: &definition.unit.scale * factor,
+                offset: definition.unit.offset.clone(),
+                dimension: definition.unit.dimension
+            })
+        })
+    }
+
+    pub fn contains(&self, symbol : &str) -> bool{
+        self.get(symbol).is_some()
+    }
+
+    ///
+    /// The symbols of the units, without their prefixed forms
+    ///
+    pub fn symbols(&self) -> impl Iterator<Item = &str>{
+        self.units.keys().map(String::as_str)
+    }
+}
+
+///
+/// `magnitude` in `unit`, a plain number if the unit is dimensionless
+///
+fn quantity(magnitude : Value, unit : Unit, mode : NumericMode) -> Result<Value, OperationError>{
+    if unit.dimension().is_dimensionless(){
+        return scaled(&magnitude, &unit.scale(), mode);
+    }
+    Ok(Value::Quantity(Box::new(Quantity{ magnitude, unit })))
+}
+
+///
+/// `unit` on its own, a quantity of magnitude 1
+///
+pub(crate) fn unit_quantity(unit : NamedUnit, mode : NumericMode) -> Value{
+    Value::Quantity(Box::new(Quantity{ magnitude: constant(&BigRational::one(), mode), unit: unit.into() }))
+}
+
+///
+/// The magnitude and unit of a value, plain values have the empty unit
+///
+fn parts(value : &Value) -> (&Value, Unit){
+    match value{
+        Value::Quantity(q) => (&q.magnitude, q.unit.clone()),
+        _ => (value, Unit::default())
+    }
+}
+
+///
+/// `factor` as a number of the kind literals have in `mode`
+///
+fn constant(factor : &BigRational, mode : NumericMode) -> Value{
+    match mode{
+        NumericMode::Integer if factor.is_integer() => Value::Integer(factor.to_integer()),
+        NumericMode::Rational => Value::Rational(factor.clone()),
+        NumericMode::Decimal { .. } => Value::Decimal(decimal::from_rational(factor, &mode.decimal_context())),
+        NumericMode::Interval => Value::Interval(Interval::enclosing(factor)),
+        _ => Value::Number(factor.to_f64().unwrap_or(f64::NAN))
+    }
+}
+
+///
+/// `factor` as a number to combine with `value`, intervals enclose it so that their bounds stay guaranteed
+///
+pub(crate) fn constant_for(value : &Value, factor : &BigRational, mode : NumericMode) -> Value{
+    match value{
+        Value::Interval(_) => Value::Interval(Interval::enclosing(factor)),
+        _ => constant(factor, mode)
+    }
+}
+
+fn scaled(value : &Value, factor : &BigRational, mode : NumericMode) -> Result<Value, OperationError>{
+    if factor.is_one(){
+        return Ok(value.clone());
+    }
+    Value::arithmetic(BinOp::Mul, value, &constant_for(value, factor, mode), mode)
+}
+
+fn shifted(value : Value, shift : &BigRational, mode : NumericMode) -> Result<Value, OperationError>{
+    if shift.is_zero(){
+        return Ok(value);
+    }
+    Value::arithmetic(BinOp::Add, &value, &constant_for(&value, shift, mode), mode)
+}
+
+///
+/// The exact value of a real number, floats are read as the shortest decimal that rounds to them
+/// and intervals holding a single number are that number
+///
+fn exact_value(value : &Value) -> Option<BigRational>{
+    match value{
+        Value::Integer(i) => Some(BigRational::from_integer(i.clone())),
+        Value::Rational(r) => Some(r.clone()),
+        Value::Decimal(d) => decimal::to_rational(d),
+        Value::Number(n) if n.is_finite() => decimal::to_rational(&BigDecimal::from_str(&n.to_string()).ok()?),
+        Value::Interval(i) if i.lo() == i.hi() => BigRational::from_float(i.lo()),
+        _ => None
+    }
+}
+
+///
+/// Apply one of the arithmetic operators to operands of which at least one is a quantity
+///
+/// Products and quotients combine the units of their operands. Sums, differences, remainders and uncertainties need operands
+/// of the same dimension, the right one is converted to the unit of the left one as a difference, so that
+/// `20 °C + 5 K` is `25 °C`. When both are temperatures on scales with an offset they are absolute, so their
+/// difference is one in kelvins, `0 °C - 32 °F` is `0 K`, and they cannot be added. Powers need a plain exponent
+/// that leaves integer exponents to the units
+///
+pub(crate) fn arithmetic(op : BinOp, l : &Value, r : &Value, mode : NumericMode) -> Result<Value, OperationError>{
+    let ((lm, lu), (rm, ru)) = (parts(l), parts(r));
+    match op{
+        BinOp::Mul | BinOp::Div => {
+            let magnitude = Value::arithmetic(op, lm, rm, mode)?;
+            let unit = lu.times(&ru, if op == BinOp::Mul { 1 } else { -1 }).ok_or(OperationError::UnitPower)?;
+            quantity(magnitude, unit, mode)
+        },
+        BinOp::Add | BinOp::Sub if !lu.offset().is_zero() && !ru.offset().is_zero() => {
+            if op == BinOp::Add{
+                return Err(OperationError::AbsoluteTemperatures);
+            }
+            // Taken in the unit of the left one as comparisons are, so that equal temperatures are 0 K apart
+            let (lm, rm) = in_same_unit(l, r)?;
+            let difference = scaled(&Value::arithmetic(op, &lm, &rm, mode)?, &lu.scale(), mode)?;
+            quantity(difference, NamedUnit::new("K", BigRational::one(), Dimension::TEMPERATURE).into(), mode)
+        },
+        BinOp::Add | BinOp::Sub | BinOp::IntDiv | BinOp::Mod | BinOp::PlusMinus => {
+            if lu.dimension() != ru.dimension(){
+                return Err(OperationError::IncompatibleUnits);
+            }
+            let rm = scaled(rm, &(ru.scale() / lu.scale()), mode)?;
+            let magnitude = Value::arithmetic(op, lm, &rm, mode)?;
+            // The quotient of quantities of the same dimension is a plain number
+            if op == BinOp::IntDiv { Ok(magnitude) } else { quantity(magnitude, lu, mode) }
+        },
+        BinOp::Exp => {
+            if !ru.factors.is_empty(){
+                return Err(OperationError::IncompatibleUnits);
+            }
+            let exponent = exact_value(rm).ok_or(OperationError::TypeMismatch)?;
+            let unit = lu.pow(&exponent).ok_or(OperationError::UnitPower)?;
+            quantity(Value::arithmetic(op, lm, rm, mode)?, unit, mode)
+        },
+        _ => unreachable!("{:?} is not an arithmetic operator", op.symbol())
+    }
+}
+
+///
+/// Express `value` in the unit of `target`, only the unit of `target` matters and not its magnitude
+///
+/// Unlike arithmetic, the conversion accounts for the offset of affine units, so `100 °C to °F` is `212 °F`
+///
+pub(crate) fn convert(value : &Value, target : &Value, mode : NumericMode) -> Result<Value, OperationError>{
+    let Value::Quantity(target) = target else{
+        return Err(OperationError::IncompatibleUnits);
+    };
+    let (magnitude, unit) = parts(value);
+    if unit.dimension() != target.unit.dimension(){
+        return Err(OperationError::IncompatibleUnits);
+    }
+
+    let scale = target.unit.scale();
+    let factor = unit.scale() / &scale;
+    let shift = (unit.offset() - target.unit.offset()) / scale;
+    let magnitude = shifted(scaled(magnitude, &factor, mode)?, &shift, mode)?;
+    Ok(Value::Quantity(Box::new(Quantity{ magnitude, unit: target.unit.clone() })))
+}
+
+///
+/// The magnitudes of both operands in the unit of the left one, so that they can be compared, affine units included
+///
+pub(crate) fn in_same_unit(l : &Value, r : &Value) -> Result<(Value, Value), OperationError>{
+    let ((lm, lu), (rm, ru)) = (parts(l), parts(r));
+    if lu.dimension() != ru.dimension(){
+        return Err(OperationError::IncompatibleUnits);
+    }
+
+    let scale = lu.scale();
+    let (factor, shift) = (ru.scale() / &scale, (ru.offset() - lu.offset()) / scale);
+    let float = |r : &BigRational| r.to_f64().unwrap_or(f64::NAN);
+    // Exact magnitudes are converted exactly, whatever the numeric mode
+    let rm = match rm{
+        Value::Number(n) => Value::Number(n * float(&factor) + float(&shift)),
+        Value::Complex(z) => Value::complex(z * float(&factor) + float(&shift)),
+        Value::Interval(i) => {
+            let scaled = interval::arithmetic(BinOp::Mul, *i, Interval::enclosing(&factor))?;
+            Value::Interval(interval::arithmetic(BinOp::Add, scaled, Interval::enclosing(&shift))?)
+        },
+        Value::Measurement(m) => {
+            let scaled = measurement::arithmetic(BinOp::Mul, m.clone(), Measurement::exact(float(&factor)))?;
+            Value::Measurement(measurement::arithmetic(BinOp::Add, scaled, Measurement::exact(float(&shift)))?)
+        },
+        m => Value::Rational(exact_value(m).ok_or(OperationError::TypeMismatch)? * factor + shift)
+    };
+    Ok((lm.clone(), rm))
+}
+
+///
+/// Describe the unit of an operand for error messages, e.g `kg (mass)`
+///
+pub(crate) fn describe(value : &Value) -> String{
+    let Value::Quantity(q) = value else{
+        return value.type_name().to_string();
+    };
+    match q.unit.dimension().name(){
+        Some(name) => format!("{} ({name})", q.unit),
+        None => q.unit.to_string()
+    }
+}
+
+///
+/// The unit named `symbol` worth `value`, e.g `unit furlong = 201.168 m`. Only prefixes and offsets are not kept,
+/// a unit defined from `°C` is a difference of temperatures
+///
+pub(crate) fn definition(symbol : &str, value : &Value) -> Result<NamedUnit, String>{
+    let (magnitude, unit) = parts(value);
+    let magnitude = exact_value(magnitude).ok_or_else(|| format!("expected a finite number or quantity, found {}", magnitude.type_name()))?;
+    if !magnitude.is_positive(){
+        return Err("a unit must be positive".into());
+    }
+    Ok(NamedUnit::new(symbol, magnitude * unit.scale(), unit.dimension()))
+}
+
+///
+/// Parse an exact rational written as a decimal or a fraction of decimals, e.g `0.3048` or `5/9`
+///
+fn exact(text : &str) -> BigRational{
+    let parse = |digits : &str| {
+        decimal::to_rational(&BigDecimal::from_str(digits).expect("built-in unit sizes are decimals")).expect("built-in unit sizes are in range")
+    };
+    match text.split_once('/'){
+        Some((numer, denom)) => parse(numer) / parse(denom),
+        None => parse(text)
+    }
+}
+
+fn register_builtins(registry : &mut UnitRegistry){
+    let d = |exponents : [i32; 7]| Dimension(exponents);
+    let (speed, volume) = (d([1, 0, -1, 0, 0, 0, 0]), d([3, 0, 0, 0, 0, 0, 0]));
+    let (energy, pressure) = (d([2, 1, -2, 0, 0, 0, 0]), d([-1, 1, -2, 0, 0, 0, 0]));
+    let (voltage, resistance) = (d([2, 1, -3, -1, 0, 0, 0]), d([2, 1, -3, -2, 0, 0, 0]));
+
+    // Symbol, size in SI base units, dimension and whether SI prefixes apply
+    let units = [
+        // SI base units, the kilogram is a prefixed gram
+        ("m", "1", Dimension::LENGTH, true),
+        ("g", "1/1000", Dimension::MASS, true),
+        ("s", "1", Dimension::TIME, true),
+        ("A", "1", Dimension::CURRENT, true),
+        ("K", "1", Dimension::TEMPERATURE, true),
+        ("mol", "1", Dimension::AMOUNT, true),
+        ("cd", "1", Dimension::LUMINOSITY, true),
+        // Derived SI units
+        ("Hz", "1", d([0, 0, -1, 0, 0, 0, 0]), true),
+        ("N", "1", d([1, 1, -2, 0, 0, 0, 0]), true),
+        ("J", "1", energy, true),
+        ("W", "1", d([2, 1, -3, 0, 0, 0, 0]), true),
+        ("Pa", "1", pressure, true),
+        ("C", "1", d([0, 0, 1, 1, 0, 0, 0]), true),
+        ("V", "1", voltage, true),
+        ("Ω", "1", resistance, true),
+        ("ohm", "1", resistance, true),
+        ("L", "1/1000", volume, true),
+        ("eV", "1.602176634e-19", energy, true),
+        ("Wh", "3600", energy, true),
+        ("cal", "4.184", energy, true),
+        ("bar", "100000", pressure, true),
+        // Time
+        ("min", "60", Dimension::TIME, false),
+        ("h", "3600", Dimension::TIME, false),
+        ("day", "86400", Dimension::TIME, false),
+        // Imperial and US customary units
+        ("in", "0.0254", Dimension::LENGTH, false),
+        ("ft", "0.3048", Dimension::LENGTH, false),
+        ("yd", "0.9144", Dimension::LENGTH, false),
+        ("mi", "1609.344", Dimension::LENGTH, false),
+        ("gal", "0.003785411784", volume, false),
+        ("lb", "0.45359237", Dimension::MASS, false),
+        ("oz", "0.028349523125", Dimension::MASS, false),
+        ("mph", "0.44704", speed, false),
+        ("kn", "1852/3600", speed, false),
+        ("atm", "101325", pressure, false),
+        // Pound-force per square inch
+        ("psi", "4.4482216152605/0.00064516", pressure, false)
+    ];
+    for (symbol, scale, dimension, prefixable) in units{
+        registry.insert(NamedUnit::new(symbol, exact(scale), dimension), prefixable).expect("built-in unit symbols are identifiers");
+    }
+
+    // Affine temperature scales
+    let celsius = NamedUnit{ offset: exact("273.15"), ..NamedUnit::new("°C", BigRational::one(), Dimension::TEMPERATURE) };
+    // 0 °F is 459.67 °F above absolute zero
+    let fahrenheit = NamedUnit{ offset: exact("459.67") * exact("5/9"), ..NamedUnit::new("°F", exact("5/9"), Dimension::TEMPERATURE) };
+    for (unit, alias) in [(celsius, "degC"), (fahrenheit, "degF")]{
+        registry.define(NamedUnit{ symbol: alias.to_string(), ..unit.clone() }).expect("built-in unit symbols are identifiers");
+        registry.define(unit).expect("built-in unit symbols are identifiers");
+    }
+}
@@ -0,0 +1,745 @@
+use std::cmp::Ordering;
+
+use bigdecimal::{BigDecimal, Context};
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::decimal;
+use crate::evaluator::NumericMode;
+use crate::expression::BinOp;
+use crate::interval::{self, Interval};
+use crate::lexer::NumberLiteral;
+use crate::format::Format;
+use crate::measurement::{self, Measurement};
+use crate::units::{self, Quantity};
+
+/// Largest power of ten a literal may carry to be read exactly, e.g `1e10000`, and a decimal may reach
+pub(crate) const MAX_EXACT_EXPONENT : u64 = 10_000;
+
+/// Largest size, in bits, of the result of an exact power before falling back to floats
+pub(crate) const MAX_EXACT_POW_BITS : u64 = 1 << 20;
+
+/// Largest power of ten the result of a decimal power may reach before falling back to floats
+const MAX_DECIMAL_POW_EXPONENT : u64 = 1_000_000_000_000_000;
+
+///
+/// The result of evaluating an expression, and what variables hold
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value{
+    Number(f64),
+    /// An integer of arbitrary size, produced in [`NumericMode::Integer`](crate::evaluator::NumericMode::Integer)
+    Integer(BigInt),
+    /// An exact fraction, produced in [`NumericMode::Rational`](crate::evaluator::NumericMode::Rational)
+    Rational(BigRational),
+    /// A decimal rounded to the precision of [`NumericMode::Decimal`](crate::evaluator::NumericMode::Decimal)
+    Decimal(BigDecimal),
+    /// A number with a non zero imaginary part, see [`Value::complex`]
+    Complex(Complex64),
+    /// A number with a unit, see [`units`](crate::units)
+    Quantity(Box<Quantity>),
+    /// Numbers known to lie between two bounds, produced in [`NumericMode::Interval`](crate::evaluator::NumericMode::Interval)
+    /// and by `[lo, hi]`
+    Interval(Interval),
+    /// A number with a standard uncertainty, produced by `value +- sigma`
+    Measurement(Measurement),
+    Bool(bool)
+}
+
+///
+/// Why an operator could not be applied to its operands
+///
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum OperationError{
+    /// The operator does not apply to the types of the operands
+    TypeMismatch,
+    /// The operands are quantities of different dimensions, e.g `1 kg + 1 s`
+    IncompatibleUnits,
+    /// A sum of two temperatures on scales with an offset, e.g `20 °C + 20 °C`
+    AbsoluteTemperatures,
+    /// A power or product would give a unit a fractional or too large exponent, e.g `(1 m)^0.5`
+    UnitPower,
+    /// The divisor is an interval holding zero
+    IntervalDivision,
+    /// A power of an interval holding negative numbers, or zero, has no real bounds, e.g `[-1, 1]^0.5`
+    IntervalPower,
+    /// The uncertainty given to a measurement is negative, e.g `1 +- -0.1`
+    NegativeUncertainty,
+    /// An uncertainty given to an interval, or that is one, e.g `[1, 2] +- 0.1`
+    IntervalUncertainty,
+    DivideByZero,
+    InfinityDivision
+}
+
+///
+/// Two numeric operands, converted to a common representation
+///
+enum Operands{
+    Floats(f64, f64),
+    Integers(BigInt, BigInt),
+    Rationals(BigRational, BigRational),
+    Decimals(BigDecimal, BigDecimal),
+    Complexes(Complex64, Complex64),
+    Intervals(Interval, Interval),
+    Measurements(Measurement, Measurement)
+}
+
+impl Operands{
+    ///
+    /// Operations involving a complex number are done on complex numbers, those involving an interval
+    /// on intervals, those involving a measurement on measurements, those mixing a float with another number
+    /// on floats, those mixing an integer with a rational on rationals, and those mixing a decimal with an
+    /// exact number on decimals of the precision of `context`
+    ///
+    fn of(l : &Value, r : &Value, context : &Context) -> Option<Self>{
+        use Value::{Complex, Decimal, Integer, Interval, Measurement, Rational};
+        match (l, r){
+            (Complex(_), _) | (_, Complex(_)) => Some(Self::Complexes(l.as_complex()?, r.as_complex()?)),
+            (Interval(_), _) | (_, Interval(_)) => Some(Self::Intervals(l.as_interval()?, r.as_interval()?)),
+            (Measurement(_), _) | (_, Measurement(_)) => Some(Self::Measurements(l.as_measurement()?, r.as_measurement()?)),
+            (Integer(x), Integer(y)) => Some(Self::Integers(x.clone(), y.clone())),
+            (Rational(_) | Integer(_), Rational(_) | Integer(_)) => Some(Self::Rationals(l.to_rational()?, r.to_rational()?)),
+            (Decimal(_), Decimal(_) | Rational(_) | Integer(_)) | (Rational(_) | Integer(_), Decimal(_)) => {
+                Some(Self::Decimals(l.to_decimal(context), r.to_decimal(context)))
+            },
+            _ => Some(Self::Floats(l.as_number()?, r.as_number()?))
+        }
+    }
+}
+
+impl Value{
+    ///
+    /// Name of the type of the value, as used in error messages
+    ///
+    pub fn type_name(&self) -> &'static str{
+        match self{
+            Self::Number(_) | Self::Integer(_) | Self::Rational(_) | Self::Decimal(_) => "number",
+            Self::Quantity(_) => "quantity",
+            Self::Complex(_) => "complex number",
+            Self::Interval(_) => "interval",
+            Self::Measurement(_) => "uncertain number",
+            Self::Bool(_) => "boolean"
+        }
+    }
+
+    ///
+    /// The value as a float, exact numbers are rounded. Complex numbers have none
+    ///
+    pub fn as_number(&self) -> Option<f64>{
+        match self{
+            Self::Number(n) => Some(*n),
+            Self::Integer(i) => Some(i.to_f64().unwrap_or(f64::NAN)),
+            Self::Rational(r) => Some(rational_to_f64(r)),
+            Self::Decimal(d) => Some(decimal::to_f64(d)),
+            _ => None
+        }
+    }
+
+    ///
+    /// The value as a complex number, real numbers have a zero imaginary part
+    ///
+    pub fn as_complex(&self) -> Option<Complex64>{
+        match self{
+            Self::Complex(z) => Some(*z),
+            _ => self.as_number().map(Complex64::from)
+        }
+    }
+
+    ///
+    /// The value as an interval, exact numbers are enclosed between the floats around them.
+    /// Complex numbers and NaN have none
+    ///
+    pub fn as_interval(&self) -> Option<Interval>{
+        match self{
+            Self::Interval(i) => Some(*i),
+            Self::Number(n) => Interval::new(*n, *n),
+            Self::Integer(_) | Self::Rational(_) | Self::Decimal(_) => match self.to_rational(){
+                Some(r) => Some(Interval::enclosing(&r)),
+                None => self.as_number().map(Interval::around)
+            },
+            _ => None
+        }
+    }
+
+    ///
+    /// The value written with `format`, the default format writes it as its `Display` does
+    ///
+    pub fn format(&self, format : &Format) -> String{
+        format.value(self)
+    }
+
+    ///
+    /// The value as a measurement, other real numbers have no uncertainty
+    ///
+    pub fn as_measurement(&self) -> Option<Measurement>{
+        match self{
+            Self::Measurement(m) => Some(m.clone()),
+            _ => self.as_number().map(Measurement::exact)
+        }
+    }
+
+    ///
+    /// A decimal, or the float nearest to it if its exponent is beyond [`MAX_EXACT_EXPONENT`]
+    ///
+    /// Sums and comparisons of decimals write out the zeros between their exponents, which larger
+    /// exponents would make take as long as they like
+    ///
+    pub(crate) fn decimal(d : BigDecimal) -> Self{
+        if decimal::in_range(&d){
+            Self::Decimal(d)
+        }
+        else{
+            Self::Number(decimal::to_f64(&d))
+        }
+    }
+
+    ///
+    /// A complex number, or a real [`Value::Number`] if its imaginary part is zero
+    ///
+    pub fn complex(z : Complex64) -> Self{
+        if z.im == 0.0{
+            Self::Number(z.re)
+        }
+        else{
+            Self::Complex(z)
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool>{
+        match self{
+            Self::Bool(b) => Some(*b),
+            _ => None
+        }
+    }
+
+    ///
+    /// Whether the value is known without rounding, a float is not even when it
+    /// comes from exact operands, e.g a transcendental function of a rational.
+    /// Neither is a decimal, which is rounded to its precision, nor an interval or a measurement.
+    /// A quantity is exact when its magnitude is
+    ///
+    pub fn is_exact(&self) -> bool{
+        match self{
+            Self::Quantity(q) => q.magnitude.is_exact(),
+            _ => !matches!(self, Self::Number(_) | Self::Decimal(_) | Self::Complex(_) | Self::Interval(_) | Self::Measurement(_))
+        }
+    }
+
+    ///
+    /// The exact value of a number literal, or `None` if its exponent is too large to expand
+    ///
+    pub fn exact_literal(literal : &NumberLiteral) -> Option<BigRational>{
+        if literal.radix != 10{
+            return BigInt::parse_bytes(literal.digits.as_bytes(), literal.radix).map(BigRational::from_integer);
+        }
+
+        let (mantissa, exponent) = match literal.digits.split_once('e'){
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+            None => (literal.digits.as_str(), 0)
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        let digits = BigInt::parse_bytes(format!("0{int}{frac}").as_bytes(), 10)?;
+        let scale = exponent.checked_sub(frac.len() as i64)?;
+        if scale.unsigned_abs() > MAX_EXACT_EXPONENT{
+            return None;
+        }
+
+        let power = num_traits::pow(BigInt::from(10), scale.unsigned_abs() as usize);
+        Some(if scale >= 0{
+            BigRational::from_integer(digits * power)
+        }
+        else{
+            BigRational::new(digits, power)
+        })
+    }
+
+    ///
+    /// The value of a number literal rounded to `context`, or `None` if its exponent is beyond [`MAX_EXACT_EXPONENT`]
+    ///
+    pub(crate) fn decimal_literal(literal : &NumberLiteral, context : &Context) -> Option<BigDecimal>{
+        let exact = if literal.radix != 10{
+            BigDecimal::from(BigInt::parse_bytes(literal.digits.as_bytes(), literal.radix)?)
+        }
+        else{
+            literal.digits.parse().ok()?
+        };
+        if !decimal::in_range(&exact){
+            return None;
+        }
+        Some(decimal::round(exact, context))
+    }
+
+    ///
+    /// Apply one of the arithmetic operators `+ - * / // % ^`, or `+-`, in `mode`
+    ///
+    /// Decimal results are rounded to the precision of the mode, and in complex mode
+    /// powers of negative numbers are complex instead of NaN
+    ///
+    pub(crate) fn arithmetic(op : BinOp, l : &Value, r : &Value, mode : NumericMode) -> Result<Value, OperationError>{
+        if matches!(l, Value::Quantity(_)) || matches!(r, Value::Quantity(_)){
+            return units::arithmetic(op, l, r, mode);
+        }
+        if op == BinOp::PlusMinus{
+            return measurement::plus_minus(l, r);
+        }
+        let context = mode.decimal_context();
+        match Operands::of(l, r, &context).ok_or(OperationError::TypeMismatch)?{
+            Operands::Floats(x, y) if op == BinOp::Exp && mode == NumericMode::Complex && x < 0.0 && y.fract() != 0.0 => {
+                complex_arithmetic(op, x.into(), y.into())
+            },
+            Operands::Floats(x, y) => float_arithmetic(op, x, y),
+            Operands::Integers(x, y) => integer_arithmetic(op, x, y),
+            Operands::Rationals(x, y) => rational_arithmetic(op, x, y),
+            Operands::Decimals(x, y) => decimal_arithmetic(op, x, y, &context),
+            Operands::Complexes(x, y) => complex_arithmetic(op, x, y),
+            Operands::Intervals(x, y) => interval::arithmetic(op, x, y).map(Value::Interval),
+            Operands::Measurements(x, y) => measurement::arithmetic(op, x, y).map(Value::Measurement)
+        }
+    }
+
+    ///
+    /// Order two real numbers, `None` if either is NaN
+    ///
+    /// Integers, rationals and decimals are compared exactly, quantities in the unit of `l`.
+    /// Intervals are ordered only when every number of one is below every number of the other,
+    /// measurements by their values
+    ///
+    pub(crate) fn compare(l : &Value, r : &Value) -> Result<Option<Ordering>, OperationError>{
+        use Value::{Decimal, Integer, Interval, Measurement, Quantity, Rational};
+        match (l, r){
+            (Quantity(_), _) | (_, Quantity(_)) => {
+                let (l, r) = units::in_same_unit(l, r)?;
+                Self::compare(&l, &r)
+            },
+            (Interval(_), _) | (_, Interval(_)) => {
+                let (x, y) = l.as_interval().zip(r.as_interval()).ok_or(OperationError::TypeMismatch)?;
+                Ok(interval::compare(&x, &y))
+            },
+            (Measurement(_), _) | (_, Measurement(_)) => {
+                let (x, y) = l.as_measurement().zip(r.as_measurement()).ok_or(OperationError::TypeMismatch)?;
+                Ok(x.value().partial_cmp(&y.value()))
+            },
+            (Integer(x), Integer(y)) => Ok(Some(x.cmp(y))),
+            (Decimal(x), Decimal(y)) => Ok(Some(x.cmp(y))),
+            (Integer(_) | Rational(_) | Decimal(_), Integer(_) | Rational(_) | Decimal(_)) => match l.to_rational().zip(r.to_rational()){
+                Some((x, y)) => Ok(Some(x.cmp(&y))),
+                None => Ok(l.as_number().partial_cmp(&r.as_number()))
+            },
+            _ => {
+                let (x, y) = l.as_number().zip(r.as_number()).ok_or(OperationError::TypeMismatch)?;
+                Ok(x.partial_cmp(&y))
+            }
+        }
+    }
+
+    ///
+    /// The exact value of an integer, rational or decimal, `None` for a decimal too large or too small to be a fraction
+    ///
+    fn to_rational(&self) -> Option<BigRational>{
+        match self{
+            Self::Integer(i) => Some(BigRational::from_integer(i.clone())),
+            Self::Rational(r) => Some(r.clone()),
+            Self::Decimal(d) => decimal::to_rational(d),
+            _ => unreachable!("only exact numbers are converted to rationals")
+        }
+    }
+
+    ///
+    /// The value of an integer, rational or decimal as a decimal of the precision of `context`
+    ///
+    fn to_decimal(&self, context : &Context) -> BigDecimal{
+        match self{
+            Self::Integer(i) => decimal::round(BigDecimal::from(i.clone()), context),
+            Self::Rational(r) => decimal::from_rational(r, context),
+            Self::Decimal(d) => d.clone(),
+            _ => unreachable!("only exact numbers are converted to decimals")
+        }
+    }
+
+    ///
+    /// Compare values of the same type for equality, numbers are equal whatever their representation
+    ///
+    pub(crate) fn equals(l : &Value, r : &Value) -> Result<bool, OperationError>{
+        match (l, r){
+            (Value::Bool(a), Value::Bool(b)) => Ok(a == b),
+            (Value::Quantity(_), _) | (_, Value::Quantity(_)) => {
+                let (l, r) = units::in_same_unit(l, r)?;
+                Self::equals(&l, &r)
+            },
+            (Value::Complex(_), _) | (_, Value::Complex(_)) => {
+                let (x, y) = l.as_complex().zip(r.as_complex()).ok_or(OperationError::TypeMismatch)?;
+                Ok(x == y)
+            },
+            _ => Ok(Self::compare(l, r)? == Some(Ordering::Equal))
+        }
+    }
+
+    pub(crate) fn negate(&self) -> Option<Value>{
+        match self{
+            Self::Number(n) => Some(Self::Number(-n)),
+            Self::Integer(i) => Some(Self::Integer(-i)),
+            Self::Quantity(q) => Some(Self::Quantity(Box::new(Quantity{ magnitude: q.magnitude.negate()?, unit: q.unit.clone() }))),
+            Self::Rational(r) => Some(Self::Rational(-r)),
+            Self::Decimal(d) => Some(Self::Decimal(-d)),
+            Self::Complex(z) => Some(Self::Complex(-z)),
+            Self::Interval(i) => Some(Self::Interval(i.negate())),
+            Self::Measurement(m) => Some(Self::Measurement(m.negate())),
+            Self::Bool(_) => None
+        }
+    }
+}
+
+fn rational_to_f64(r : &BigRational) -> f64{
+    r.to_f64().unwrap_or(f64::NAN)
+}
+
+fn float_arithmetic(op : BinOp, x : f64, y : f64) -> Result<Value, OperationError>{
+    if matches!(op, BinOp::IntDiv | BinOp::Mod) && y == 0.0{
+        return Err(OperationError::DivideByZero);
+    }
+    let result = match op{
+        BinOp::Add => x + y,
+        BinOp::Sub => x - y,
+        BinOp::Mul => x * y,
+        BinOp::Div => {
+            if y == 0.0{
+                return Err(OperationError::DivideByZero);
+            }
+            if x.is_infinite() && y.is_infinite(){
+                return Err(OperationError::InfinityDivision);
+            }
+            x / y
+        },
+        BinOp::IntDiv => (x / y).floor(),
+        BinOp::Mod => {
+            // The remainder of `%` on floats has the sign of the dividend, that of the operator the sign of the divisor
+            let rest = x % y;
+            if rest != 0.0 && (rest < 0.0) != (y < 0.0) { rest + y } else { rest }
+        },
+        BinOp::Exp => x.powf(y),
+        _ => unreachable!("{:?} is not an arithmetic operator", op.symbol())
+    };
+    Ok(Value::Number(result))
+}
+
+///
+/// Integers stay exact, except for the quotient of integers that do not divide each other
+/// and for negative or huge powers, which are computed on floats
+///
+fn integer_arithmetic(op : BinOp, x : BigInt, y : BigInt) -> Result<Value, OperationError>{
+    if matches!(op, BinOp::Div | BinOp::IntDiv | BinOp::Mod) && y.is_zero(){
+        return Err(OperationError::DivideByZero);
+    }
+    let result = match op{
+        BinOp::Add => x + y,
+        BinOp::Sub => x - y,
+        BinOp::Mul => x * y,
+        BinOp::Div => {
+            if !x.is_multiple_of(&y){
+                return Ok(Value::Number(rational_to_f64(&BigRational::new(x, y))));
+            }
+            x / y
+        },
+        BinOp::IntDiv => x.div_floor(&y),
+        BinOp::Mod => x.mod_floor(&y),
+        BinOp::Exp => return integer_pow(x, y),
+        _ => unreachable!("{:?} is not an arithmetic operator", op.symbol())
+    };
+    Ok(Value::Integer(result))
+}
+
+fn integer_pow(base : BigInt, exponent : BigInt) -> Result<Value, OperationError>{
+    if base.is_zero() && exponent.is_negative(){
+        return Err(OperationError::DivideByZero);
+    }
+    if let Some(e) = exponent.to_u32(){
+        if base.bits() * u64::from(e) <= MAX_EXACT_POW_BITS{
+            return Ok(Value::Integer(base.pow(e)));
+        }
+    }
+    float_arithmetic(BinOp::Exp, base.to_f64().unwrap_or(f64::NAN), exponent.to_f64().unwrap_or(f64::NAN))
+}
+
+fn rational_arithmetic(op : BinOp, x : BigRational, y : BigRational) -> Result<Value, OperationError>{
+    if matches!(op, BinOp::IntDiv | BinOp::Mod) && y.is_zero(){
+        return Err(OperationError::DivideByZero);
+    }
+    let result = match op{
+        BinOp::Add => x + y,
+        BinOp::Sub => x - y,
+        BinOp::Mul => x * y,
+        BinOp::Div => {
+            if y.is_zero(){
+                return Err(OperationError::DivideByZero);
+            }
+            x / y
+        },
+        BinOp::IntDiv => (x / y).floor(),
+        BinOp::Mod => {
+            let quotient = (&x / &y).floor();
+            x - y * quotient
+        },
+        BinOp::Exp => return rational_pow(x, y),
+        _ => unreachable!("{:?} is not an arithmetic operator", op.symbol())
+    };
+    Ok(Value::Rational(result))
+}
+
+///
+/// Integer powers stay exact unless the result would be huge, others are computed on floats
+///
+fn rational_pow(base : BigRational, exponent : BigRational) -> Result<Value, OperationError>{
+    let integer_exponent = exponent.is_integer().then(|| exponent.to_integer().to_i32()).flatten();
+
+    if let Some(e) = integer_exponent{
+        let size = base.numer().bits().max(base.denom().bits()) * u64::from(e.unsigned_abs());
+        if size <= MAX_EXACT_POW_BITS{
+            if base.is_zero() && e < 0{
+                return Err(OperationError::DivideByZero);
+            }
+            return Ok(Value::Rational(base.pow(e)));
+        }
+    }
+
+    float_arithmetic(BinOp::Exp, rational_to_f64(&base), rational_to_f64(&exponent))
+}
+
+fn complex_arithmetic(op : BinOp, x : Complex64, y : Complex64) -> Result<Value, OperationError>{
+    let result = match op{
+        BinOp::Add => x + y,
+        BinOp::Sub => x - y,
+        BinOp::Mul => x * y,
+        BinOp::Div => {
+            if y == Complex64::from(0.0){
+                return Err(OperationError::DivideByZero);
+            }
+            x / y
+        },
+        BinOp::IntDiv | BinOp::Mod => {
+            return Err(OperationError::TypeMismatch);
+        },
+        BinOp::Exp => return complex_pow(x, y),
+        _ => unreachable!("{:?} is not an arithmetic operator", op.symbol())
+    };
+    Ok(Value::complex(result))
+}
+
+///
+/// The principal value of `base^exponent`
+///
+fn complex_pow(base : Complex64, exponent : Complex64) -> Result<Value, OperationError>{
+    if base == Complex64::from(0.0){
+        if exponent.re > 0.0{
+            return Ok(Value::Number(0.0));
+        }
+        if exponent == Complex64::from(0.0){
+            return Ok(Value::Number(1.0));
+        }
+        return Err(OperationError::DivideByZero);
+    }
+    // Integer powers by multiplication, so that e.g `i^2` is exactly -1
+    if exponent.im == 0.0 && exponent.re.fract() == 0.0 && exponent.re.abs() <= i32::MAX as f64{
+        return Ok(Value::complex(base.powi(exponent.re as i32)));
+    }
+    Ok(Value::complex(base.powc(exponent)))
+}
+
+fn decimal_arithmetic(op : BinOp, x : BigDecimal, y : BigDecimal, context : &Context) -> Result<Value, OperationError>{
+    let result = match op{
+        BinOp::Add => decimal::round(x + y, context),
+        BinOp::Sub => decimal::round(x - y, context),
+        BinOp::Mul => decimal::round(x * y, context),
+        BinOp::Div => {
+            if y.is_zero(){
+                return Err(OperationError::DivideByZero);
+            }
+            decimal::div(&x, &y, context)
+        },
+        BinOp::IntDiv | BinOp::Mod => {
+            if y.is_zero(){
+                return Err(OperationError::DivideByZero);
+            }
+            // The quotient is taken exactly, so that the rounding of `x / y` cannot move it to the next integer
+            let (Some(x), Some(y)) = (decimal::to_rational(&x), decimal::to_rational(&y)) else{
+                return float_arithmetic(op, decimal::to_f64(&x), decimal::to_f64(&y));
+            };
+            let quotient = (&x / &y).floor();
+            let result = if op == BinOp::IntDiv { quotient } else { x - y * quotient };
+            decimal::from_rational(&result, context)
+        },
+        BinOp::Exp => return decimal_pow(x, y, context),
+        _ => unreachable!("{:?} is not an arithmetic operator", op.symbol())
+    };
+    Ok(Value::decimal(result))
+}
+
+///
+/// Integer powers are computed by multiplication, other powers of positive numbers through logarithms.
+/// Results too large for a decimal, and powers of negative numbers that are not real, are computed on floats
+///
+fn decimal_pow(base : BigDecimal, exponent : BigDecimal, context : &Context) -> Result<Value, OperationError>{
+    let float = |base : &BigDecimal, exponent : &BigDecimal| float_arithmetic(BinOp::Exp, decimal::to_f64(base), decimal::to_f64(exponent));
+
+    if base.is_zero(){
+        return match exponent.sign(){
+            num_bigint::Sign::Minus => Err(OperationError::DivideByZero),
+            num_bigint::Sign::NoSign => Ok(Value::Decimal(BigDecimal::one())),
+            num_bigint::Sign::Plus => Ok(Value::Decimal(BigDecimal::zero()))
+        };
+    }
+
+    let integer_exponent = exponent.is_integer().then(|| exponent.to_i64()).flatten();
+    if let Some(e) = integer_exponent{
+        let magnitude = (base.order_of_magnitude().unsigned_abs() + 1).saturating_mul(e.unsigned_abs());
+        if magnitude <= MAX_DECIMAL_POW_EXPONENT{
+            return Ok(Value::decimal(decimal::powi(&base, e, context)));
+        }
+        return float(&base, &exponent);
+    }
+
+    if base.is_negative(){
+        return float(&base, &exponent);
+    }
+    match decimal::powf(&base, &exponent, context){
+        Some(result) => Ok(Value::decimal(result)),
+        None => float(&base, &exponent)
+    }
+}
+
+///
+/// Write `r` as an exact decimal if it has one, as a fraction otherwise
+///
+fn fmt_rational(r : &BigRational, f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+    if r.is_integer(){
+        return write!(f, "{}", r.numer());
+    }
+
+    // Only denominators made of twos and fives have a finite decimal expansion
+    let (mut rest, mut twos, mut fives) = (r.denom().clone(), 0usize, 0usize);
+    let (two, five) = (BigInt::from(2), BigInt::from(5));
+    while rest.is_multiple_of(&two){
+        rest /= &two;
+        twos += 1;
+    }
+    while rest.is_multiple_of(&five){
+        rest /= &five;
+        fives += 1;
+    }
+    if !rest.is_one(){
+        return write!(f, "{}/{}", r.numer(), r.denom());
+    }
+
+    let places = twos.max(fives);
+    let scaled = (r * BigRational::from_integer(num_traits::pow(BigInt::from(10), places))).to_integer();
+    let digits = format!("{:0>width$}", scaled.abs(), width = places + 1);
+    let (int, frac) = digits.split_at(digits.len() - places);
+    let sign = if r.is_negative() { "-" } else { "" };
+    write!(f, "{sign}{int}.{frac}")
+}
+
+///
+/// Write `d` in positional notation, unless it would need many zeros to place its digits
+///
+fn fmt_decimal(d : &BigDecimal, f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+    let magnitude = d.order_of_magnitude();
+    // With a negative scale the digits would be followed by zeros
+    let exact_digits = d.as_bigint_and_exponent().1 >= 0;
+    if d.is_zero() || (magnitude >= -7 && (magnitude < 21 || exact_digits)){
+        write!(f, "{}", d.to_plain_string())
+    }
+    else{
+        write!(f, "{d:e}")
+    }
+}
+
+///
+/// Write `z` as `a+bi`, or `bi` without real part
+///
+fn fmt_complex(z : &Complex64, f : &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+    if z.re == 0.0{
+        write!(f, "{}i", z.im)
+    }
+    else if z.im.is_sign_negative(){
+        write!(f, "{}-{}i", z.re, -z.im)
+    }
+    else{
+        write!(f, "{}+{}i", z.re, z.im)
+    }
+}
+
+impl From<f64> for Value{
+    fn from(n : f64) -> Self{
+        Self::Number(n)
+    }
+}
+
+impl From<BigInt> for Value{
+    fn from(i : BigInt) -> Self{
+        Self::Integer(i)
+    }
+}
+
+impl From<BigRational> for Value{
+    fn from(r : BigRational) -> Self{
+        Self::Rational(r)
+    }
+}
+
+impl From<BigDecimal> for Value{
+    fn from(d : BigDecimal) -> Self{
+        Self::Decimal(d)
+    }
+}
+
+impl From<Complex64> for Value{
+    fn from(z : Complex64) -> Self{
+        Self::complex(z)
+    }
+}
+
+impl From<Interval> for Value{
+    fn from(i : Interval) -> Self{
+        Self::Interval(i)
+    }
+}
+
+impl From<Measurement> for Value{
+    fn from(m : Measurement) -> Self{
+        Self::Measurement(m)
+    }
+}
+
+impl From<bool> for Value{
+    fn from(b : bool) -> Self{
+        Self::Bool(b)
+    }
+}
+
+///
+/// Lets numeric results be compared against plain floats
+///
+impl PartialEq<f64> for Value{
+    fn eq(&self, other : &f64) -> bool{
+        self.as_number() == Some(*other)
+    }
+}
+
+impl std::fmt::Display for Value{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result{
+        match self{
+            Self::Number(n) => write!(f, "{n}"),
+            Self::Integer(i) => write!(f, "{i}"),
+            Self::Quantity(q) => write!(f, "{q}"),
+            Self::Rational(r) => fmt_rational(r, f),
+            Self::Decimal(d) => fmt_decimal(d, f),
+            Self::Complex(z) => fmt_complex(z, f),
+            Self::Interval(i) => write!(f, "{i}"),
+            Self::Measurement(m) => write!(f, "{m}"),
+            Self::Bool(b) => write!(f, "{b}")
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use ise_rust_calc_lib::evaluator::StatementOutcome;
+use ise_rust_calc_lib::{CalcError, Calculator, Value};
+
+#[test]
+fn evaluates_expressions() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(calc.eval("1 + 2 * 3").unwrap(), 7.0);
+    assert_eq!(calc.eval("2 ^ 3").unwrap(), 8.0);
+}
+
+#[test]
+fn keeps_variables_between_calls() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(calc.eval("foo = 4").unwrap(), 4.0);
+    assert_eq!(calc.eval("bar = foo * 2").unwrap(), 8.0);
+    assert_eq!(calc.eval("foo + bar").unwrap(), 12.0);
+    assert_eq!(
+        calc.evaluator().get_variable("bar"),
+        Some(Value::Number(8.0))
+    );
+}
+
+#[test]
+fn definitions_run_through_execute() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(
+        calc.execute("f(x) = x^2").unwrap(),
+        StatementOutcome::FunctionDefined {
+            name: "f".into(),
+            params: vec!["x".into()]
+        }
+    );
+    assert_eq!(calc.eval("f(3)").unwrap(), 9.0);
+    assert_eq!(
+        calc.execute("a = f(2)").unwrap().into_value(),
+        Some(4.0.into())
+    );
+    assert_eq!(calc.execute("   ").unwrap_err().code(), "E205");
+
+    // Definitions have no value, eval leaves them unrun
+    let input = "unit league = 3 mi";
+    let err = calc.eval(input).unwrap_err();
+    assert_eq!(err.code(), "E213");
+    assert_eq!(
+        err.contextualize(input),
+        "unit league = 3 mi\n     ------------- << [E213] Expected a value, a definition has none"
+    );
+    assert!(!calc.evaluator().units().contains("league"));
+    assert_eq!(calc.eval("g(x) = 2x; g(f(2))").unwrap(), 8.0);
+    assert_eq!(calc.eval("   ").unwrap_err().code(), "E205");
+}
+
+#[test]
+fn reports_arithmetic_errors() {
+    let mut calc = Calculator::new();
+
+    assert!(matches!(calc.eval("1 / 0"), Err(CalcError::Arithmetic(_))));
+    assert!(matches!(calc.eval("foz"), Err(CalcError::Arithmetic(_))));
+}
+
+#[test]
+fn errors_carry_codes_and_context() {
+    let mut calc = Calculator::new();
+
+    let err = calc.eval("1 + * 2").unwrap_err();
+    assert!(matches!(err, CalcError::Parse(_)));
+    assert_eq!(err.code(), "E201");
+
+    let err = calc.eval("4 / 0").unwrap_err();
+    assert_eq!(err.code(), "E301");
+    assert_eq!(err.to_string(), "error[E301]: Division by Zero");
+    assert_eq!(
+        err.contextualize("4 / 0"),
+        "4 / 0\n----- << [E301] Division by Zero"
+    );
+}
+
+#[test]
+fn identifiers_with_digits_and_underscores() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(calc.eval("v_max = 3").unwrap(), 3.0);
+    assert_eq!(calc.eval("x1 = v_max * 2").unwrap(), 6.0);
+    assert_eq!(
+        calc.eval("sin(x1 - x1) + _unused_2").unwrap_err().message(),
+        "Undefined variable: \"_unused_2\""
+    );
+}
+
+#[test]
+fn comparisons_and_conditionals() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(calc.eval("1 < 2").unwrap(), Value::Bool(true));
+    assert_eq!(calc.eval("2 <= 1 or 3 != 3").unwrap(), Value::Bool(false));
+    assert_eq!(calc.eval("not false and true").unwrap(), Value::Bool(true));
+
+    // The untaken branches are never evaluated
+    assert_eq!(calc.eval("x = 0; x == 0 ? 5 : 1 / x").unwrap(), 5.0);
+    assert_eq!(calc.eval("if(x > 0, 1 / x, -1)").unwrap(), -1.0);
+    assert_eq!(
+        calc.eval("x != 0 and 1 / x > 2").unwrap(),
+        Value::Bool(false)
+    );
+    assert_eq!(calc.eval("x == 0 or 1 / x > 2").unwrap(), Value::Bool(true));
+    assert_eq!(calc.eval("if(true, 1 / x, 0)").unwrap_err().code(), "E301");
+
+    // Piecewise definitions, including recursion
+    calc.run("tax(i) = i <= 10000 ? 0 : i <= 40000 ? (i - 10000) * 0.2 : 6000 + (i - 40000) * 0.4")
+        .unwrap();
+    assert_eq!(calc.eval("tax(5000) + tax(20000)").unwrap(), 2000.0);
+    assert_eq!(calc.eval("tax(50000)").unwrap(), 10000.0);
+    calc.run("fact(n) = if(n <= 1, 1, n fact(n - 1))").unwrap();
+    assert_eq!(calc.eval("fact(5)").unwrap(), 120.0);
+}
+
+#[test]
+fn type_errors() {
+    let mut calc = Calculator::new();
+
+    let err = calc.eval("1 + (2 < 3)").unwrap_err();
+    assert_eq!(err.code(), "E309");
+    assert_eq!(
+        err.contextualize("1 + (2 < 3)"),
+        "1 + (2 < 3)\n  - << [E309] Cannot add boolean to number"
+    );
+
+    let err = calc.eval("b = true; 2b").unwrap_err();
+    assert_eq!(
+        err.message(),
+        "In statement 2: Cannot multiply number by boolean"
+    );
+    assert_eq!(err.span().begin.offset, 11);
+    assert_eq!(
+        calc.eval("-b").unwrap_err().message(),
+        "Cannot negate boolean"
+    );
+    assert_eq!(
+        calc.eval("1 == true").unwrap_err().message(),
+        "Cannot compare number with boolean using \"==\""
+    );
+    assert_eq!(
+        calc.eval("1 and b").unwrap_err().message(),
+        "Expected a boolean for \"and\", found number"
+    );
+
+    let err = calc.eval("1 ? 2 : 3").unwrap_err();
+    assert_eq!(
+        err.message(),
+        "Expected a boolean for the condition, found number"
+    );
+    assert_eq!(err.span().end.offset, 1);
+
+    let err = calc.eval("max(1, b)").unwrap_err();
+    assert_eq!(
+        err.message(),
+        "\"max\" expects a number as argument 2, found boolean"
+    );
+    assert_eq!(err.span().begin.offset, 7);
+
+    assert_eq!(calc.eval("b == (1 < 2)").unwrap(), Value::Bool(true));
+    assert_eq!(calc.eval("b").unwrap().to_string(), "true");
+}
@@ -0,0 +1,6 @@
+use ise_rust_calc_lib::Calculator;
+
+/// Evaluates the input and writes its value
+pub fn display(calc: &mut Calculator, input: &str) -> String {
+    calc.eval(input).unwrap().to_string()
+}
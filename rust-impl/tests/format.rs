@@ -0,0 +1,155 @@
+use ise_rust_calc_lib::evaluator::NumericMode;
+use ise_rust_calc_lib::format::{Format, Notation};
+use ise_rust_calc_lib::Calculator;
+
+fn formatted(calc: &mut Calculator, input: &str, format: &Format) -> String {
+    calc.eval(input).unwrap().format(format)
+}
+
+#[test]
+fn default_format_matches_display() {
+    let mut calc = Calculator::new();
+    let format = Format::default();
+
+    for input in [
+        "1 / 3",
+        "-1234567.125",
+        "1e300",
+        "INF",
+        "[1, 2.5]",
+        "9.81 +- 0.02",
+        "(5 +- 0.25) m",
+        "3 ft to in",
+        "1 < 2",
+    ] {
+        let value = calc.eval(input).unwrap();
+        assert_eq!(value.format(&format), value.to_string(), "{input}");
+    }
+
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Rational);
+    for input in ["1/3", "-7/8 m", "2^70"] {
+        let value = calc.eval(input).unwrap();
+        assert_eq!(value.format(&format), value.to_string(), "{input}");
+    }
+}
+
+#[test]
+fn notations() {
+    let mut calc = Calculator::new();
+    let fixed = Format::new(Notation::Fixed(2));
+    let significant = Format::new(Notation::Significant(3));
+    let scientific = Format::new(Notation::Scientific(3));
+    let engineering = Format::new(Notation::Engineering(3));
+
+    assert_eq!(formatted(&mut calc, "PI", &fixed), "3.14");
+    assert_eq!(formatted(&mut calc, "1.5", &fixed), "1.50");
+    assert_eq!(formatted(&mut calc, "-2.675", &fixed), "-2.68");
+    assert_eq!(
+        formatted(&mut calc, "1e21 + 0.5", &fixed),
+        "1000000000000000000000.00"
+    );
+
+    assert_eq!(formatted(&mut calc, "PI", &significant), "3.14");
+    assert_eq!(formatted(&mut calc, "1.5", &significant), "1.50");
+    assert_eq!(formatted(&mut calc, "123456", &significant), "123000");
+    assert_eq!(formatted(&mut calc, "9.999", &significant), "10.0");
+    assert_eq!(
+        formatted(&mut calc, "0.000123456", &significant),
+        "0.000123"
+    );
+    assert_eq!(formatted(&mut calc, "0", &significant), "0.00");
+
+    assert_eq!(formatted(&mut calc, "12345", &scientific), "1.23e4");
+    assert_eq!(
+        formatted(&mut calc, "-0.000123456", &scientific),
+        "-1.23e-4"
+    );
+    assert_eq!(formatted(&mut calc, "9.999", &scientific), "1.00e1");
+    assert_eq!(formatted(&mut calc, "0", &scientific), "0.00e0");
+
+    assert_eq!(formatted(&mut calc, "12345", &engineering), "12.3e3");
+    assert_eq!(formatted(&mut calc, "0.000123456", &engineering), "123e-6");
+    assert_eq!(formatted(&mut calc, "1.5", &engineering), "1.50e0");
+
+    // Non-finite numbers are written as they are
+    assert_eq!(formatted(&mut calc, "-INF", &fixed), "-inf");
+}
+
+#[test]
+fn exact_values_round_exactly() {
+    let mut calc = Calculator::new();
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Rational);
+
+    assert_eq!(
+        formatted(&mut calc, "2/3", &Format::new(Notation::Fixed(3))),
+        "0.667"
+    );
+    assert_eq!(
+        formatted(&mut calc, "-1/8", &Format::new(Notation::Fixed(2))),
+        "-0.13"
+    );
+    assert_eq!(
+        formatted(
+            &mut calc,
+            "10^30 + 1/3",
+            &Format::new(Notation::Significant(35))
+        ),
+        "1000000000000000000000000000000.3333"
+    );
+    assert_eq!(
+        formatted(&mut calc, "1/7000", &Format::new(Notation::Scientific(2))),
+        "1.4e-4"
+    );
+
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Integer);
+    assert_eq!(
+        formatted(&mut calc, "2^70", &Format::new(Notation::Engineering(4))),
+        "1.181e21"
+    );
+}
+
+#[test]
+fn separators() {
+    let mut calc = Calculator::new();
+    let grouped = Format {
+        grouping: Some(','),
+        ..Format::default()
+    };
+    assert_eq!(
+        formatted(&mut calc, "-1234567.25", &grouped),
+        "-1,234,567.25"
+    );
+    assert_eq!(formatted(&mut calc, "123", &grouped), "123");
+
+    let german = Format {
+        notation: Notation::Fixed(2),
+        ..Format::locale("de-DE").unwrap()
+    };
+    assert_eq!(formatted(&mut calc, "1234567.891", &german), "1.234.567,89");
+    assert_eq!(formatted(&mut calc, "[1.5, 2.5]", &german), "[1,50; 2,50]");
+    assert_eq!(formatted(&mut calc, "1500 m", &german), "1.500,00 m");
+    assert_eq!(formatted(&mut calc, "1.5 +- 0.25", &german), "1,50 ± 0,25");
+    assert_eq!(
+        formatted(&mut calc, "12345.6", &Format::locale("fr").unwrap()),
+        "12\u{202f}345,6"
+    );
+    assert!(Format::locale("tlh").is_none());
+}
+
+#[test]
+fn intervals_round_outward() {
+    let mut calc = Calculator::new();
+    let format = Format::new(Notation::Fixed(1));
+
+    assert_eq!(formatted(&mut calc, "[1.04, 1.06]", &format), "[1.0, 1.1]");
+    assert_eq!(
+        formatted(&mut calc, "[-1.06, -1.04]", &format),
+        "[-1.1, -1.0]"
+    );
+
+    // Results are written with the format of the evaluator
+    calc.evaluator_mut()
+        .set_format(Format::new(Notation::Significant(2)));
+    let value = calc.eval("2 / 3").unwrap();
+    assert_eq!(calc.evaluator().display(&value), "0.67");
+}
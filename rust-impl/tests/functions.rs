@@ -0,0 +1,235 @@
+use ise_rust_calc_lib::evaluator::{AngleMode, NumericMode};
+use ise_rust_calc_lib::functions::{Arity, FunctionError, InvalidFunctionName};
+use ise_rust_calc_lib::Calculator;
+
+#[test]
+fn builtins() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(calc.eval("nrt(3, 27)").unwrap(), 3.0);
+    assert_eq!(calc.eval("log(2, 8)").unwrap(), 3.0);
+    assert_eq!(calc.eval("sin(0) + cos(0)").unwrap(), 1.0);
+    assert_eq!(calc.eval("log(10, 0)").unwrap_err().code(), "E303");
+}
+
+#[test]
+fn math_library() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(calc.eval("sqrt(16) + cbrt(-8)").unwrap(), 2.0);
+    assert_eq!(
+        calc.eval("exp(0) + ln(1) + log10(1000) + log2(8)").unwrap(),
+        7.0
+    );
+    assert_eq!(calc.eval("abs(-2) * sign(-3) + sign(0)").unwrap(), -2.0);
+    assert_eq!(
+        calc.eval("floor(-1.5) + ceil(1.2) + round(2.5) + trunc(-1.7)")
+            .unwrap(),
+        2.0
+    );
+    assert_eq!(
+        calc.eval("min(3, 1, 2) + max(4) + clamp(9, 0, 5)").unwrap(),
+        10.0
+    );
+    assert_eq!(calc.eval("hypot(3, 4)").unwrap(), 5.0);
+    assert_eq!(
+        calc.eval("atan2(1, 1)").unwrap(),
+        std::f64::consts::FRAC_PI_4
+    );
+    assert_eq!(calc.eval("asin(1)").unwrap(), std::f64::consts::FRAC_PI_2);
+    assert_eq!(calc.eval("acos(1) + atan(0)").unwrap(), 0.0);
+    assert_eq!(calc.eval("tanh(0) + sinh(0) + cosh(0)").unwrap(), 1.0);
+    assert_eq!(calc.eval("asinh(0) + acosh(1) + atanh(0)").unwrap(), 0.0);
+    assert_eq!(calc.eval("nrt(3, -27)").unwrap(), -3.0);
+}
+
+#[test]
+fn math_domain_errors() {
+    let mut calc = Calculator::new();
+
+    for input in [
+        "sqrt(-1)",
+        "ln(-1)",
+        "asin(2)",
+        "acos(-1.5)",
+        "acosh(0.5)",
+        "atanh(1)",
+        "nrt(2, -4)",
+        "log(1, 5)",
+        "clamp(1, 5, 0)",
+    ] {
+        assert_eq!(calc.eval(input).unwrap_err().code(), "E307", "{input}");
+    }
+    assert_eq!(calc.eval("ln(0)").unwrap_err().code(), "E303");
+
+    let err = calc.eval("sqrt(-1)").unwrap_err();
+    assert_eq!(
+        err.message(),
+        "Invalid argument to \"sqrt\": square root of a negative number"
+    );
+    assert_eq!(calc.eval("min()").unwrap_err().code(), "E306");
+    assert_eq!(calc.eval("clamp(1, 2)").unwrap_err().code(), "E306");
+}
+
+#[test]
+fn angle_modes() {
+    let mut calc = Calculator::new();
+    let close = |calc: &mut Calculator, input: &str, expected: f64| {
+        let value = calc.eval(input).unwrap().as_number().unwrap();
+        assert!((value - expected).abs() < 1e-12, "{input} is {value}");
+    };
+
+    // Literals with a suffix are converted to the angle mode
+    close(&mut calc, "sin(30°)", 0.5);
+    close(&mut calc, "cos(100grad)", 0.0);
+    close(&mut calc, "deg(180) + rad(1)", std::f64::consts::PI + 1.0);
+    assert_eq!(calc.eval("1.2rad").unwrap(), 1.2);
+
+    calc.evaluator_mut().set_angle_mode(AngleMode::Degrees);
+    assert_eq!(calc.evaluator().angle_mode(), AngleMode::Degrees);
+    close(&mut calc, "sin(30) + cos(60)", 1.0);
+    close(&mut calc, "tan(45)", 1.0);
+    close(&mut calc, "asin(0.5)", 30.0);
+    close(&mut calc, "acos(0) + atan(1) + atan2(1, -1)", 270.0);
+    close(&mut calc, "rad(PI)", 180.0);
+    assert_eq!(calc.eval("100grad").unwrap(), 90.0);
+    assert_eq!(calc.eval("deg(90)").unwrap(), 90.0);
+    // So do the polar forms of complex numbers
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Complex);
+    close(&mut calc, "arg(i) + arg(-1)", 270.0);
+    close(&mut calc, "im(polar(2, 30))", 1.0);
+    close(&mut calc, "arg(polar(1, 60))", 60.0);
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Float);
+
+    calc.evaluator_mut().set_angle_mode(AngleMode::Gradians);
+    close(&mut calc, "sin(100) + asin(1)", 101.0);
+    close(&mut calc, "90°", 100.0);
+
+    // Degrees and gradians convert exactly in the exact modes
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Rational);
+    assert_eq!(calc.eval("30°").unwrap().to_string(), "100/3");
+    close(&mut calc, "sin(100)", 1.0);
+}
+
+#[test]
+fn call_errors() {
+    let mut calc = Calculator::new();
+
+    let err = calc.eval("1 + foo(1)").unwrap_err();
+    assert_eq!(err.code(), "E305");
+    assert_eq!(
+        err.contextualize("1 + foo(1)"),
+        "1 + foo(1)\n    ------ << [E305] Undefined function: \"foo\""
+    );
+
+    let err = calc.eval("sin(1, 2)").unwrap_err();
+    assert_eq!(err.code(), "E306");
+    assert_eq!(err.message(), "\"sin\" expects 1 argument(s), got 2");
+}
+
+#[test]
+fn arity() {
+    assert!(Arity::Fixed(2).accepts(2));
+    assert!(!Arity::Fixed(2).accepts(1));
+    assert!(Arity::Range(1, 3).accepts(3));
+    assert!(!Arity::Range(1, 3).accepts(0));
+    assert!(Arity::Variadic { min: 1 }.accepts(10));
+    assert!(!Arity::Variadic { min: 1 }.accepts(0));
+    assert_eq!(Arity::Range(1, 3).to_string(), "1 to 3");
+}
+
+#[test]
+fn host_functions() {
+    let mut calc = Calculator::new();
+    let functions = calc.evaluator_mut().functions_mut();
+
+    functions
+        .register("sum", Arity::Variadic { min: 0 }, |args| {
+            Ok(args.iter().sum())
+        })
+        .unwrap();
+    functions
+        .register("half", Arity::Fixed(1), |args| Ok(args[0] / 2.0))
+        .unwrap();
+    functions
+        .register("safe_sqrt", Arity::Fixed(1), |args| {
+            if args[0] < 0.0 {
+                Err(FunctionError::Domain("negative input".into()))
+            } else {
+                Ok(args[0].sqrt())
+            }
+        })
+        .unwrap();
+
+    assert_eq!(calc.eval("sum()").unwrap(), 0.0);
+    assert_eq!(calc.eval("sum(1, 2, 3, half(8))").unwrap(), 10.0);
+
+    let err = calc.eval("safe_sqrt(-4)").unwrap_err();
+    assert_eq!(err.code(), "E307");
+    assert_eq!(
+        err.message(),
+        "Invalid argument to \"safe_sqrt\": negative input"
+    );
+
+    assert_eq!(
+        calc.evaluator_mut()
+            .functions_mut()
+            .register("not valid", Arity::Fixed(0), |_| Ok(0.0)),
+        Err(InvalidFunctionName("not valid".into()))
+    );
+}
+
+#[test]
+fn user_functions() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(calc.run("f(x, y) = x^2 + y; f(3, 4)").unwrap(), vec![13.0]);
+    // Parameters shadow variables, other variables are read at call time
+    calc.eval("x = 10; k = 1; g(x) = x + k").unwrap();
+    assert_eq!(calc.eval("g(2)").unwrap(), 3.0);
+    calc.eval("k = 5").unwrap();
+    assert_eq!(calc.eval("g(2) + x").unwrap(), 17.0);
+    // Definitions can be replaced, and shadow the built-ins
+    calc.run("f(x) = 2x; sin(x) = 0").unwrap();
+    assert_eq!(calc.eval("f(f(1)) + sin(1)").unwrap(), 4.0);
+    assert_eq!(calc.eval("zero() = 0; zero()").unwrap(), 0.0);
+    // A definition has no value
+    assert!(calc.run("one() = 1").unwrap().is_empty());
+
+    let err = calc.eval("f(1, 2)").unwrap_err();
+    assert_eq!(err.code(), "E306");
+    assert_eq!(err.message(), "\"f\" expects 1 argument(s), got 2");
+}
+
+#[test]
+fn user_function_errors() {
+    let mut calc = Calculator::new();
+    calc.run("inv(x) = 1 / x; h(x) = inv(x) + 1; loop(x) = loop(x + 1)")
+        .unwrap();
+
+    // Reported at the outermost call of the evaluated input
+    let err = calc.eval("2 * h(0)").unwrap_err();
+    assert_eq!(err.code(), "E301");
+    assert_eq!(
+        err.contextualize("2 * h(0)"),
+        "2 * h(0)\n    ---- << [E301] In call to \"h\": Division by Zero"
+    );
+
+    calc.run("count(n) = n <= 0 ? 0 : 1 + count(n - 1)")
+        .unwrap();
+    assert_eq!(calc.eval("count(900)").unwrap(), 900.0);
+
+    let err = calc.eval("loop(0)").unwrap_err();
+    assert_eq!(err.code(), "E308");
+    assert_eq!(
+        err.message(),
+        "In call to \"loop\": Recursion too deep in \"loop\""
+    );
+
+    let err = calc.eval("f(x, x) = x").unwrap_err();
+    assert_eq!(err.code(), "E208");
+    assert_eq!(err.span().begin.offset, 5);
+    assert_eq!(calc.eval("f(x, 2) = x").unwrap_err().code(), "E203");
+    assert_eq!(calc.eval("f(x,) = x").unwrap_err().code(), "E203");
+    assert_eq!(calc.eval("f(x) =").unwrap_err().code(), "E205");
+}
@@ -0,0 +1,182 @@
+use ise_rust_calc_lib::evaluator::NumericMode;
+use ise_rust_calc_lib::interval::Interval;
+use ise_rust_calc_lib::{Calculator, Value};
+
+mod common;
+use common::display;
+
+fn interval_calculator() -> Calculator {
+    let mut calc = Calculator::new();
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Interval);
+    calc
+}
+
+fn interval(calc: &mut Calculator, input: &str) -> Interval {
+    match calc.eval(input).unwrap() {
+        Value::Interval(i) => i,
+        other => panic!("{input:?} is not an interval: {other}"),
+    }
+}
+
+#[test]
+fn interval_arithmetic() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(display(&mut calc, "[1, 2] + [3, 4]"), "[4, 6]");
+    assert_eq!(display(&mut calc, "[1, 2] - [3, 4]"), "[-3, -1]");
+    assert_eq!(display(&mut calc, "[1, 2] * [-3, 4]"), "[-6, 8]");
+    assert_eq!(display(&mut calc, "[1, 2] / [2, 4]"), "[0.25, 1]");
+    assert_eq!(display(&mut calc, "-[1, 2]"), "[-2, -1]");
+    assert_eq!(display(&mut calc, "[7, 8] // 2"), "[3, 4]");
+    assert_eq!(display(&mut calc, "[7, 8] % 3"), "[1, 2]");
+    assert_eq!(display(&mut calc, "[7, 9] % 3"), "[0, 3]");
+
+    // Powers know the sign of the base, unlike the product of an interval by itself
+    assert_eq!(display(&mut calc, "[-1, 2]^2"), "[0, 4]");
+    assert_eq!(display(&mut calc, "[-1, 2] * [-1, 2]"), "[-2, 4]");
+    assert_eq!(display(&mut calc, "[-2, -1]^3"), "[-8, -1]");
+    assert_eq!(display(&mut calc, "[1, 2]^-1"), "[0.5, 1]");
+    assert_eq!(
+        display(&mut calc, "[0, 4]^0.5").split(',').next(),
+        Some("[0")
+    );
+
+    // Bounds that are not floats are rounded outward
+    let third = interval(&mut calc, "[1, 2] / 3");
+    assert!(third.lo() <= 1.0 / 3.0 && third.hi() > 2.0 / 3.0);
+    let x = interval(&mut calc, "x = [1.9, 2.1]; x^2");
+    assert!(x.lo() < 1.9 * 1.9 && x.lo() > 3.6 - 1e-12);
+    assert!(x.hi() > 4.41 && x.hi() < 4.41 + 1e-12);
+
+    assert_eq!(display(&mut calc, "lo([1, 3]) + width([1, 3])"), "3");
+    assert_eq!(display(&mut calc, "mid([1, 3])"), "2");
+    assert_eq!(
+        display(&mut calc, "[1.9, 2.1] mm to m")
+            .split(' ')
+            .next_back(),
+        Some("m")
+    );
+}
+
+#[test]
+fn interval_comparisons() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(calc.eval("[1, 2] < [3, 4]").unwrap(), Value::Bool(true));
+    assert_eq!(calc.eval("[1, 2] > 0").unwrap(), Value::Bool(true));
+    // Overlapping intervals are not ordered
+    assert_eq!(calc.eval("[1, 3] > 2").unwrap(), Value::Bool(false));
+    assert_eq!(calc.eval("[1, 3] <= 2").unwrap(), Value::Bool(false));
+    assert_eq!(calc.eval("[1, 2] == [1, 2]").unwrap(), Value::Bool(false));
+    assert_eq!(calc.eval("[2, 2] == 2").unwrap(), Value::Bool(true));
+}
+
+#[test]
+fn interval_functions() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(display(&mut calc, "sqrt([4, 9])"), "[2, 3]");
+    assert_eq!(display(&mut calc, "abs([-3, 2])"), "[0, 3]");
+    assert_eq!(display(&mut calc, "floor([1.5, 3.5])"), "[1, 3]");
+    assert_eq!(display(&mut calc, "max(1, [0, 2])"), "[1, 2]");
+
+    // Extrema within the interval bound sin and cos, monotonic segments their values at the bounds
+    let sin = interval(&mut calc, "sin([0, 4])");
+    assert_eq!(sin.hi(), 1.0);
+    assert!(sin.lo() <= 4f64.sin() && sin.lo() > 4f64.sin() - 1e-15);
+    let sin = interval(&mut calc, "sin([1, 1.5])");
+    assert!(sin.lo() <= 1f64.sin() && sin.hi() >= 1.5f64.sin() && sin.hi() < 1.0);
+    let cos = interval(&mut calc, "cos([-1, 1])");
+    assert_eq!(cos.hi(), 1.0);
+    assert!(cos.lo() <= 1f64.cos() && cos.lo() > 0.54);
+    let cos = interval(&mut calc, "cos([3, 10])");
+    assert_eq!((cos.lo(), cos.hi()), (-1.0, 1.0));
+    let cos = interval(&mut calc, "cos([-5, -4])");
+    assert!(cos.lo() <= (-4f64).cos() && cos.hi() >= (-5f64).cos() && cos.hi() < 0.3);
+    let tan = interval(&mut calc, "tan([-1, 1])");
+    assert!(tan.lo() <= -(1f64.tan()) && tan.hi() >= 1f64.tan());
+
+    assert_eq!(calc.eval("tan([1, 2])").unwrap_err().code(), "E307");
+    assert_eq!(calc.eval("sqrt([-1, 4])").unwrap_err().code(), "E307");
+    assert_eq!(calc.eval("ln([0, 1])").unwrap_err().code(), "E307");
+    assert_eq!(calc.eval("asin([0, 2])").unwrap_err().code(), "E307");
+    assert_eq!(calc.eval("factorial([1, 2])").unwrap_err().code(), "E307");
+}
+
+#[test]
+fn interval_mode_functions() {
+    let mut calc = interval_calculator();
+
+    assert!(interval(&mut calc, "log(2, 8)").contains(3.0));
+    assert!(interval(&mut calc, "nrt(3, 27)").contains(3.0));
+    let root = interval(&mut calc, "nrt(3, [-8, 27])");
+    assert!(root.contains(-2.0) && root.contains(3.0) && root.width() < 5.0 + 1e-12);
+    assert_eq!(display(&mut calc, "hypot(3, 4)"), "[5, 5]");
+    assert_eq!(
+        display(&mut calc, "hypot([-1, 1], 4)").split(',').next(),
+        Some("[4")
+    );
+    assert!(interval(&mut calc, "atan2(1, 1)").contains(std::f64::consts::FRAC_PI_4));
+    assert!(interval(&mut calc, "atan2(1, -1)").contains(3.0 * std::f64::consts::FRAC_PI_4));
+    assert!(interval(&mut calc, "atan2(-1, -1)").contains(-3.0 * std::f64::consts::FRAC_PI_4));
+    assert_eq!(display(&mut calc, "clamp([1, 9], 2, 5)"), "[2, 5]");
+    assert_eq!(
+        display(&mut calc, "factorial(5) + binomial(10, 3)"),
+        "[240, 240]"
+    );
+    assert!(interval(&mut calc, "factorial(25)").contains(1.5511210043330986e25));
+    assert_eq!(display(&mut calc, "re(2) + im(2)"), "[2, 2]");
+
+    for (input, message) in [
+        ("log(1, 3)", "logarithm base must be positive and not 1, an interval base must not hold 1"),
+        ("nrt(2, -4)", "root of an interval holding negative numbers, which is only real for a single odd root number"),
+        ("clamp(1, 5, 2)", "clamp lower bound is above the upper bound"),
+        ("factorial([1, 2])", "factorial of an interval that is not a single number"),
+        ("polar(1, 2)", "polar builds a complex number, which intervals do not support"),
+    ] {
+        let err = calc.eval(input).unwrap_err();
+        assert_eq!(err.code(), "E307", "{input}");
+        assert!(err.message().ends_with(message), "{input}: {}", err.message());
+    }
+}
+
+#[test]
+fn interval_errors() {
+    let mut calc = Calculator::new();
+
+    let err = calc.eval("[1, 2] / [-1, 1]").unwrap_err();
+    assert_eq!(err.code(), "E311");
+    assert_eq!(
+        err.message(),
+        "Cannot divide [1, 2] by [-1, 1], the divisor holds zero"
+    );
+    assert_eq!(calc.eval("1 // [0, 1]").unwrap_err().code(), "E311");
+    assert_eq!(calc.eval("[-1, 1]^-2").unwrap_err().code(), "E311");
+    assert_eq!(calc.eval("[-1, 4]^0.5").unwrap_err().code(), "E311");
+    assert_eq!(calc.eval("[2, 1]").unwrap_err().code(), "E311");
+    assert_eq!(calc.eval("[1, true]").unwrap_err().code(), "E309");
+    assert_eq!(calc.eval("[1, 2] + true").unwrap_err().code(), "E309");
+}
+
+#[test]
+fn interval_mode() {
+    let mut calc = interval_calculator();
+
+    // Literals are the narrowest intervals holding them
+    assert_eq!(display(&mut calc, "1"), "[1, 1]");
+    assert_eq!(display(&mut calc, "2^10 - 0.5"), "[1023.5, 1023.5]");
+    assert_eq!(display(&mut calc, "0.1"), "[0.09999999999999999, 0.1]");
+    let sum = interval(&mut calc, "0.1 + 0.2");
+    assert!(sum.contains(0.3) && sum.lo() < sum.hi());
+    let pi = interval(&mut calc, "PI");
+    assert!(pi.lo() == std::f64::consts::PI && pi.hi() > pi.lo());
+    assert!(interval(&mut calc, "sin(PI)").contains(0.0));
+    assert!(interval(&mut calc, "sqrt(2)^2").contains(2.0));
+    assert!(interval(&mut calc, "x = [1.9, 2.1]; x - x").contains(0.0));
+
+    let feet = calc.eval("3 ft to m").unwrap().to_string();
+    assert_eq!(feet, "[0.9143999999999999, 0.9144000000000001] m");
+
+    assert_eq!(calc.eval("1 / 0").unwrap_err().code(), "E311");
+    assert!(!calc.eval("1 + 1").unwrap().is_exact());
+}
@@ -0,0 +1,340 @@
+use ise_rust_calc_lib::angle::AngleMode;
+use ise_rust_calc_lib::lexer::{
+    is_identifier, LexErrorKind, LexemeKind, Lexer, NumberLiteral, Position,
+};
+
+fn kinds(input: &str) -> Vec<LexemeKind> {
+    Lexer::new(input.to_string())
+        .all()
+        .unwrap()
+        .into_iter()
+        .map(|l| l.kind)
+        .collect()
+}
+
+fn number(value: f64, radix: u32, digits: &str) -> LexemeKind {
+    LexemeKind::Number(NumberLiteral {
+        value,
+        radix,
+        digits: digits.to_string(),
+    })
+}
+
+fn decimal(value: f64, digits: &str) -> LexemeKind {
+    number(value, 10, digits)
+}
+
+fn error(input: &str) -> LexErrorKind {
+    Lexer::new(input.to_string()).all().unwrap_err().kind
+}
+
+#[test]
+fn empty() {
+    assert_eq!(kinds(""), vec![]);
+    assert_eq!(kinds(" \t "), vec![]);
+}
+
+#[test]
+fn numbers() {
+    assert_eq!(
+        kinds("1 23 4.729"),
+        vec![
+            decimal(1.0, "1"),
+            decimal(23.0, "23"),
+            decimal(4.729, "4.729"),
+        ]
+    );
+}
+
+#[test]
+fn unexpected_character() {
+    assert_eq!(error("1 + $"), LexErrorKind::UnexpectedCharacter('$'));
+    assert_eq!(error("$ + 1"), LexErrorKind::UnexpectedCharacter('$'));
+}
+
+#[test]
+fn malformed_numbers() {
+    assert_eq!(error("1.x + 2"), LexErrorKind::MalformedNumber);
+    assert_eq!(error("1.2.3"), LexErrorKind::MalformedNumber);
+    assert_eq!(
+        error("2 * 1."),
+        LexErrorKind::Unterminated("decimal number")
+    );
+
+    let err = Lexer::new("4 + 1.x".to_string()).all().unwrap_err();
+    assert_eq!(
+        err.contextualize("4 + 1.x"),
+        "4 + 1.x\n    --- << Malformed number"
+    );
+}
+
+#[test]
+fn unicode_spans() {
+    let toks = Lexer::new("café + θ".to_string()).all().unwrap();
+    assert_eq!(toks[0].kind, LexemeKind::Word("café".to_string()));
+    assert_eq!(toks[0].span.begin, Position::new(0, 0, 0));
+    assert_eq!(toks[0].span.end, Position::new(5, 0, 4));
+    assert_eq!(toks[2].span.begin, Position::new(8, 0, 7));
+    assert_eq!(toks[2].span.end, Position::new(10, 0, 8));
+}
+
+#[test]
+fn line_tracking() {
+    let toks = Lexer::new("a +\n  bc".to_string()).all().unwrap();
+    assert_eq!(toks[2].kind, LexemeKind::Newline);
+    assert_eq!(toks[3].span.begin, Position::new(6, 1, 2));
+    assert_eq!(toks[3].span.begin.to_string(), "2:3");
+}
+
+#[test]
+fn underline_wide_characters() {
+    let source = "变量 + x";
+    let toks = Lexer::new(source.to_string()).all().unwrap();
+    assert_eq!(toks[2].span.underline(source, None), "变量 + x\n       -");
+    assert_eq!(toks[0].span.underline(source, None), "变量 + x\n----");
+}
+
+#[test]
+fn underline_multiple_lines() {
+    let source = "a\n+ (1 +\n 2";
+    let toks = Lexer::new(source.to_string()).all().unwrap();
+    let span = toks[3].span.up_to(&toks[7].span);
+    assert_eq!(
+        span.underline(source, Some("here")),
+        "2 | + (1 +\n  |   ----\n3 |  2\n  |  - << here"
+    );
+}
+
+#[test]
+fn scientific_notation() {
+    assert_eq!(kinds("6.02e23"), vec![decimal(6.02e23, "6.02e23")]);
+    assert_eq!(
+        kinds("1E-9 2e+3"),
+        vec![decimal(1e-9, "1e-9"), decimal(2e3, "2e+3")]
+    );
+    // Without digits the `e` is a word of its own
+    assert_eq!(
+        kinds("2e"),
+        vec![decimal(2.0, "2"), LexemeKind::Word("e".to_string())]
+    );
+}
+
+#[test]
+fn leading_dot_and_separators() {
+    assert_eq!(kinds(".5"), vec![decimal(0.5, ".5")]);
+    assert_eq!(kinds("1_000_000"), vec![decimal(1e6, "1000000")]);
+    assert_eq!(kinds("1_000.000_1"), vec![decimal(1000.0001, "1000.0001")]);
+}
+
+#[test]
+fn radix_literals() {
+    assert_eq!(
+        kinds("0xFF 0o17 0b1010 0xdead_beef"),
+        vec![
+            number(255.0, 16, "FF"),
+            number(15.0, 8, "17"),
+            number(10.0, 2, "1010"),
+            number(3735928559.0, 16, "deadbeef"),
+        ]
+    );
+}
+
+#[test]
+fn malformed_literals() {
+    assert_eq!(error("1__000"), LexErrorKind::MalformedNumber);
+    assert_eq!(error("1_ + 2"), LexErrorKind::MalformedNumber);
+    assert_eq!(error("1e5.3"), LexErrorKind::MalformedNumber);
+    assert_eq!(error("0b102"), LexErrorKind::MalformedNumber);
+    assert_eq!(error("0xFG"), LexErrorKind::MalformedNumber);
+    assert_eq!(error("0x_1"), LexErrorKind::MalformedNumber);
+    assert_eq!(
+        error("1 + 0x"),
+        LexErrorKind::Unterminated("hexadecimal literal")
+    );
+    assert_eq!(error("1_"), LexErrorKind::Unterminated("number"));
+    assert_eq!(error("."), LexErrorKind::UnexpectedCharacter('.'));
+
+    let err = Lexer::new("0b102 + 1".to_string()).all().unwrap_err();
+    assert_eq!(
+        err.contextualize("0b102 + 1"),
+        "0b102 + 1\n----- << Malformed number"
+    );
+}
+
+#[test]
+fn identifiers() {
+    assert_eq!(
+        kinds("x1 v_max rate2024 _tmp"),
+        vec![
+            LexemeKind::Word("x1".to_string()),
+            LexemeKind::Word("v_max".to_string()),
+            LexemeKind::Word("rate2024".to_string()),
+            LexemeKind::Word("_tmp".to_string()),
+        ]
+    );
+    // A leading digit starts a number instead
+    assert_eq!(
+        kinds("2x"),
+        vec![decimal(2.0, "2"), LexemeKind::Word("x".to_string())]
+    );
+
+    assert!(is_identifier("v_max"));
+    assert!(is_identifier("θ2"));
+    assert!(!is_identifier("2x"));
+    assert!(!is_identifier("a-b"));
+    assert!(!is_identifier(""));
+}
+
+#[test]
+fn units_and_conversions() {
+    assert_eq!(
+        kinds("20°C to °F"),
+        vec![
+            decimal(20.0, "20"),
+            LexemeKind::Word("°C".to_string()),
+            LexemeKind::To,
+            LexemeKind::Word("°F".to_string()),
+        ]
+    );
+    assert_eq!(
+        kinds("unit µin"),
+        vec![LexemeKind::Unit, LexemeKind::Word("µin".to_string())]
+    );
+    assert!(is_identifier("°C"));
+    assert!(!is_identifier("to"));
+    assert!(!is_identifier("C°"));
+}
+
+#[test]
+fn intervals() {
+    assert_eq!(
+        kinds("[1, x]"),
+        vec![
+            LexemeKind::OpenBracket,
+            decimal(1.0, "1"),
+            LexemeKind::Comma,
+            LexemeKind::Word("x".to_string()),
+            LexemeKind::CloseBracket,
+        ]
+    );
+}
+
+#[test]
+fn angle_suffixes() {
+    assert_eq!(
+        kinds("30° 1.2rad 100grad"),
+        vec![
+            decimal(30.0, "30"),
+            LexemeKind::Angle(AngleMode::Degrees),
+            decimal(1.2, "1.2"),
+            LexemeKind::Angle(AngleMode::Radians),
+            decimal(100.0, "100"),
+            LexemeKind::Angle(AngleMode::Gradians),
+        ]
+    );
+    // Only right after a number, elsewhere they are words
+    assert_eq!(
+        kinds("2 rad x°"),
+        vec![
+            decimal(2.0, "2"),
+            LexemeKind::Word("rad".to_string()),
+            LexemeKind::Word("x".to_string()),
+            LexemeKind::Word("°".to_string()),
+        ]
+    );
+    assert_eq!(
+        kinds("30°C 2radius"),
+        vec![
+            decimal(30.0, "30"),
+            LexemeKind::Word("°C".to_string()),
+            decimal(2.0, "2"),
+            LexemeKind::Word("radius".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn uncertainties() {
+    assert_eq!(
+        kinds("1 +- 2 ± 3 + -4"),
+        vec![
+            decimal(1.0, "1"),
+            LexemeKind::PlusMinus,
+            decimal(2.0, "2"),
+            LexemeKind::PlusMinus,
+            decimal(3.0, "3"),
+            LexemeKind::Plus,
+            LexemeKind::Minus,
+            decimal(4.0, "4"),
+        ]
+    );
+    // Unspaced, the sum of a negated number
+    assert_eq!(
+        kinds("3 +-2"),
+        vec![
+            decimal(3.0, "3"),
+            LexemeKind::Plus,
+            LexemeKind::Minus,
+            decimal(2.0, "2"),
+        ]
+    );
+}
+
+#[test]
+fn division_operators() {
+    assert_eq!(
+        kinds("a / b // c % d"),
+        vec![
+            LexemeKind::Word("a".to_string()),
+            LexemeKind::Slash,
+            LexemeKind::Word("b".to_string()),
+            LexemeKind::DoubleSlash,
+            LexemeKind::Word("c".to_string()),
+            LexemeKind::Percent,
+            LexemeKind::Word("d".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn comparisons_and_keywords() {
+    assert_eq!(
+        kinds("a == b != c < d <= e > f >= g = h"),
+        vec![
+            LexemeKind::Word("a".to_string()),
+            LexemeKind::DoubleEquals,
+            LexemeKind::Word("b".to_string()),
+            LexemeKind::NotEquals,
+            LexemeKind::Word("c".to_string()),
+            LexemeKind::Less,
+            LexemeKind::Word("d".to_string()),
+            LexemeKind::LessEquals,
+            LexemeKind::Word("e".to_string()),
+            LexemeKind::Greater,
+            LexemeKind::Word("f".to_string()),
+            LexemeKind::GreaterEquals,
+            LexemeKind::Word("g".to_string()),
+            LexemeKind::Equals,
+            LexemeKind::Word("h".to_string()),
+        ]
+    );
+    assert_eq!(
+        kinds("not x and if or ? : android"),
+        vec![
+            LexemeKind::Not,
+            LexemeKind::Word("x".to_string()),
+            LexemeKind::And,
+            LexemeKind::If,
+            LexemeKind::Or,
+            LexemeKind::Question,
+            LexemeKind::Colon,
+            LexemeKind::Word("android".to_string()),
+        ]
+    );
+    assert_eq!(error("1 ! 2"), LexErrorKind::UnexpectedCharacter('!'));
+    assert_eq!(
+        kinds("true false"),
+        vec![LexemeKind::True, LexemeKind::False]
+    );
+    assert!(!is_identifier("and"));
+}
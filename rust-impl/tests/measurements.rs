@@ -0,0 +1,126 @@
+use ise_rust_calc_lib::evaluator::NumericMode;
+use ise_rust_calc_lib::measurement::Measurement;
+use ise_rust_calc_lib::{Calculator, Value};
+
+mod common;
+use common::display;
+
+fn measurement(calc: &mut Calculator, input: &str) -> Measurement {
+    match calc.eval(input).unwrap() {
+        Value::Measurement(m) => m,
+        other => panic!("{input:?} is not a measurement: {other}"),
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= 1e-12 * expected.abs().max(1.0),
+        "{actual} is not {expected}"
+    );
+}
+
+#[test]
+fn uncertainty_propagation() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(display(&mut calc, "9.81 +- 0.02"), "9.81 ± 0.02");
+    assert_eq!(display(&mut calc, "9.81 ± 0.02"), "9.81 ± 0.02");
+    assert_eq!(display(&mut calc, "-(1 +- 0.1)"), "-1 ± 0.1");
+    assert_eq!(display(&mut calc, "1 + 2 +- 0.5 * 2"), "3 ± 1");
+    // Only a spaced `+-` is an uncertainty
+    assert_eq!(display(&mut calc, "3 +-2"), "1");
+    assert_eq!(display(&mut calc, "a = 5; a +-a"), "0");
+    assert_eq!(display(&mut calc, "(3 +- 0.3) + (4 +- 0.4)"), "7 ± 0.5");
+    assert_eq!(display(&mut calc, "(2 +- 0.1) * (3 +- 0.2)"), "6 ± 0.5");
+
+    let m = measurement(&mut calc, "(2 +- 0.1)^2");
+    assert_close(m.value(), 4.0);
+    assert_close(m.sigma(), 0.4);
+    let m = measurement(&mut calc, "2^(1 +- 0.1)");
+    assert_close(m.sigma(), 0.2 * std::f64::consts::LN_2);
+
+    // Comparisons use the values
+    assert_eq!(display(&mut calc, "(1 +- 0.5) < 2"), "true");
+}
+
+#[test]
+fn correlated_measurements() {
+    let mut calc = Calculator::new();
+    calc.run("x = 3 +- 0.1; y = 4 +- 0.2").unwrap();
+
+    assert_eq!(display(&mut calc, "x - x"), "0 ± 0");
+    assert_eq!(display(&mut calc, "x / x"), "1 ± 0");
+    assert_close(measurement(&mut calc, "x * x").sigma(), 0.6);
+    assert_close(measurement(&mut calc, "x + x").sigma(), 0.2);
+    assert_close(measurement(&mut calc, "x + y").sigma(), 0.1f64.hypot(0.2));
+    assert_close(measurement(&mut calc, "(x + y) - y").sigma(), 0.1);
+
+    // Through conditionals and user functions alike
+    calc.run("square(t) = t * t").unwrap();
+    assert_close(measurement(&mut calc, "square(x) - x^2").sigma(), 0.0);
+    assert_eq!(display(&mut calc, "if(x > 0, x, -x) - x"), "0 ± 0");
+
+    // Giving a measurement a further uncertainty adds an independent source of error
+    assert_close(measurement(&mut calc, "(x +- 0.2) - x").sigma(), 0.2);
+}
+
+#[test]
+fn measurement_functions() {
+    let mut calc = Calculator::new();
+
+    let m = measurement(&mut calc, "sin(1 +- 0.1)");
+    assert_close(m.value(), 1f64.sin());
+    assert_close(m.sigma(), 0.1 * 1f64.cos());
+    assert_close(measurement(&mut calc, "sqrt(4 +- 0.4)").sigma(), 0.1);
+    assert_close(measurement(&mut calc, "ln(2 +- 0.1)").sigma(), 0.05);
+    assert_close(measurement(&mut calc, "exp(0 +- 0.1)").sigma(), 0.1);
+    assert_close(measurement(&mut calc, "hypot(3 +- 0.5, 4)").sigma(), 0.3);
+    assert_close(measurement(&mut calc, "atan2(1 +- 0.1, 1)").sigma(), 0.05);
+    assert_eq!(display(&mut calc, "floor(2.5 +- 0.1)"), "2 ± 0");
+    assert_eq!(display(&mut calc, "max(1 +- 0.1, 2 +- 0.2, 0)"), "2 ± 0.2");
+
+    // The domain is that of the value
+    assert_eq!(calc.eval("sqrt(-1 +- 0.1)").unwrap_err().code(), "E307");
+    assert_eq!(calc.eval("ln(0 +- 0.1)").unwrap_err().code(), "E303");
+}
+
+#[test]
+fn measurements_with_units() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(display(&mut calc, "(5 +- 0.1) m"), "(5 ± 0.1) m");
+    assert_eq!(display(&mut calc, "5 m +- 2 cm"), "(5 ± 0.02) m");
+    assert_eq!(display(&mut calc, "(5 +- 0.25) m to cm"), "(500 ± 25) cm");
+    assert_eq!(calc.eval("5 m +- 2 s").unwrap_err().code(), "E310");
+}
+
+#[test]
+fn measurement_errors() {
+    let mut calc = Calculator::new();
+
+    let err = calc.eval("1 +- -0.1").unwrap_err();
+    assert_eq!(err.code(), "E307");
+    assert_eq!(
+        err.message(),
+        "Invalid argument to \"+-\": the uncertainty -0.1 is negative"
+    );
+
+    let err = calc.eval("true +- 1").unwrap_err();
+    assert_eq!(err.code(), "E309");
+    assert_eq!(
+        err.message(),
+        "Cannot give boolean an uncertainty of number"
+    );
+    // The uncertainty itself is exact
+    assert_eq!(calc.eval("1 +- (1 +- 1)").unwrap_err().code(), "E309");
+    assert_eq!(calc.eval("[1, 2] +- 1").unwrap_err().code(), "E311");
+
+    // Interval bounds already are what is known of a value
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Interval);
+    let err = calc.eval("1 +- 0.1").unwrap_err();
+    assert_eq!(err.code(), "E311");
+    assert_eq!(
+        err.message(),
+        "Cannot give [1, 1] an uncertainty of [0.09999999999999999, 0.1], intervals have bounds rather than uncertainties"
+    );
+}
@@ -0,0 +1,325 @@
+use ise_rust_calc_lib::evaluator::{NumericMode, RoundingMode};
+use ise_rust_calc_lib::{Calculator, Value};
+
+mod common;
+use common::display;
+
+fn calculator(mode: NumericMode) -> Calculator {
+    let mut calc = Calculator::new();
+    calc.evaluator_mut().set_numeric_mode(mode);
+    calc
+}
+
+#[test]
+fn rational_arithmetic_is_exact() {
+    let mut calc = calculator(NumericMode::Rational);
+
+    assert_eq!(display(&mut calc, "0.1 + 0.2"), "0.3");
+    assert_eq!(display(&mut calc, "1/3 * 3"), "1");
+    assert_eq!(display(&mut calc, "1/3 - 1/2"), "-1/6");
+    assert_eq!(display(&mut calc, "-7/8"), "-0.875");
+    assert_eq!(display(&mut calc, "2^-3 + .5"), "0.625");
+    assert_eq!(display(&mut calc, "(2/3)^2"), "4/9");
+    assert_eq!(display(&mut calc, "0x1F / 4"), "7.75");
+    assert_eq!(display(&mut calc, "1.5e2 + 1e-3"), "150.001");
+    assert_eq!(
+        display(&mut calc, "123456789012345678901234567890 + 1"),
+        "123456789012345678901234567891"
+    );
+    assert_eq!(
+        display(&mut calc, "2^100"),
+        "1267650600228229401496703205376"
+    );
+    assert_eq!(calc.eval("0.1 + 0.2 == 0.3").unwrap(), Value::Bool(true));
+    assert_eq!(calc.eval("1/3 < 0.34").unwrap(), Value::Bool(true));
+    assert!(calc.eval("x = 1/3; x * 3").unwrap().is_exact());
+
+    assert_eq!(calc.eval("1 / (1/2 - 0.5)").unwrap_err().code(), "E301");
+    assert_eq!(calc.eval("0^-1").unwrap_err().code(), "E301");
+}
+
+#[test]
+fn rational_fallback_to_float() {
+    let mut calc = calculator(NumericMode::Rational);
+
+    // Transcendental functions and constants are not exact
+    let val = calc.eval("sin(1/2)").unwrap();
+    assert!(!val.is_exact());
+    assert_eq!(val, 0.5f64.sin());
+    assert!(!calc.eval("2^0.5").unwrap().is_exact());
+    assert!(!calc.eval("1/3 + PI").unwrap().is_exact());
+
+    // Functions with rational results stay exact
+    assert_eq!(display(&mut calc, "sqrt(9/4)"), "1.5");
+    assert!(!calc.eval("sqrt(2)").unwrap().is_exact());
+    assert_eq!(display(&mut calc, "floor(-7/2) + round(5/2)"), "-1");
+    assert_eq!(display(&mut calc, "max(1/3, 0.3) + abs(-1/6)"), "0.5");
+    assert_eq!(display(&mut calc, "clamp(5/3, 0, 1) + sign(-1/7)"), "0");
+    assert_eq!(calc.eval("sqrt(-1/4)").unwrap_err().code(), "E307");
+}
+
+#[test]
+fn float_mode_is_the_default() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(calc.evaluator().numeric_mode(), NumericMode::Float);
+    assert_eq!(calc.eval("0.1 + 0.2").unwrap(), Value::Number(0.1 + 0.2));
+
+    // Values computed before changing mode are kept
+    calc.eval("third = 1/3").unwrap();
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Rational);
+    assert!(!calc.eval("third * 3").unwrap().is_exact());
+}
+
+#[test]
+fn decimal_precision_and_functions() {
+    let mut calc = calculator(NumericMode::decimal(50));
+
+    assert_eq!(
+        display(&mut calc, "sqrt(2)"),
+        "1.4142135623730950488016887242096980785696718753769"
+    );
+    assert_eq!(
+        display(&mut calc, "PI"),
+        "3.1415926535897932384626433832795028841971693993751"
+    );
+    assert_eq!(
+        display(&mut calc, "sin(1)"),
+        "0.84147098480789650665250232163029899962256306079837"
+    );
+    assert_eq!(
+        display(&mut calc, "ln(2)"),
+        "0.69314718055994530941723212145817656807550013436026"
+    );
+    assert_eq!(
+        display(&mut calc, "nrt(5, 2)"),
+        "1.1486983549970350067986269467779275894438508890978"
+    );
+    assert_eq!(
+        display(&mut calc, "1/3"),
+        "0.33333333333333333333333333333333333333333333333333"
+    );
+    assert_eq!(
+        display(&mut calc, "exp(-1000)"),
+        "5.0759588975494567652918094795743369193055992828928e-435"
+    );
+
+    // Exact results come out exact
+    assert_eq!(display(&mut calc, "log(2, 8) + nrt(3, -27)"), "0");
+    assert_eq!(display(&mut calc, "0.5 // 1"), "0");
+    assert_eq!(display(&mut calc, "3 % 1.5"), "0");
+    assert_eq!(display(&mut calc, "sin(1)^2 + cos(1)^2"), "1");
+    assert_eq!(
+        display(&mut calc, "2^100"),
+        "1267650600228229401496703205376"
+    );
+    assert_eq!(calc.eval("0.1 + 0.2 == 0.3").unwrap(), Value::Bool(true));
+    assert!(!calc.eval("1/4").unwrap().is_exact());
+
+    assert_eq!(calc.eval("1 / (0.5 - 1/2)").unwrap_err().code(), "E301");
+    assert_eq!(calc.eval("ln(0)").unwrap_err().code(), "E303");
+    assert_eq!(calc.eval("nrt(2, -4)").unwrap_err().code(), "E307");
+    assert_eq!(calc.eval("exp(1e13)").unwrap_err().code(), "E307");
+}
+
+#[test]
+fn decimal_rounding_modes() {
+    let rounded = |rounding, input| {
+        let mut calc = calculator(NumericMode::Decimal {
+            precision: 10,
+            rounding,
+        });
+        display(&mut calc, input)
+    };
+
+    assert_eq!(rounded(RoundingMode::HalfEven, "2/3"), "0.6666666667");
+    assert_eq!(rounded(RoundingMode::Down, "2/3"), "0.6666666666");
+    assert_eq!(rounded(RoundingMode::Up, "1/3"), "0.3333333334");
+    assert_eq!(rounded(RoundingMode::Floor, "-2/3"), "-0.6666666667");
+    assert_eq!(rounded(RoundingMode::Ceiling, "sqrt(2)"), "1.414213563");
+    // Literals are rounded too, before the addition
+    assert_eq!(
+        rounded(RoundingMode::HalfEven, "1234567890.5 + 1"),
+        "1234567891"
+    );
+
+    // Directed rounding does not push exact results off
+    assert_eq!(rounded(RoundingMode::Down, "nrt(3, 27)"), "3");
+    assert_eq!(rounded(RoundingMode::Down, "log10(1000)"), "3");
+}
+
+#[test]
+fn decimal_exponents_are_bounded() {
+    let mut calc = calculator(NumericMode::decimal(50));
+
+    // Beyond 1e±10000 decimals fall back to floats, rather than writing out the zeros of their exponent
+    assert_eq!(calc.eval("1e-100000000 + 1").unwrap(), 1.0);
+    assert_eq!(calc.eval("(1e-9000)^1000 + 1").unwrap(), 1.0);
+    assert_eq!(calc.eval("floor(1e100000000)").unwrap(), f64::INFINITY);
+    assert_eq!(calc.eval("1e100000000 // 3").unwrap(), f64::INFINITY);
+    assert!(calc
+        .eval("sin(1e100000000)")
+        .unwrap()
+        .as_number()
+        .unwrap()
+        .is_nan());
+
+    assert_eq!(display(&mut calc, "floor(1e9999) + 1"), "1e+9999");
+    assert_eq!(
+        calc.eval("1e9999 // 7 > 1e9998").unwrap(),
+        Value::Bool(true)
+    );
+    let err = calc.eval("sin(1e9999)").unwrap_err();
+    assert_eq!(
+        err.message(),
+        "Invalid argument to \"sin\": angle has too many integer digits to reduce it to a turn"
+    );
+}
+
+#[test]
+fn decimals_mix_with_other_numbers() {
+    let mut calc = calculator(NumericMode::Rational);
+    calc.eval("third = 1/3").unwrap();
+    calc.evaluator_mut()
+        .set_numeric_mode(NumericMode::decimal(20));
+
+    // Rationals are rounded when mixed with decimals, floats win over both
+    assert_eq!(display(&mut calc, "third + 0"), "0.33333333333333333333");
+    assert_eq!(calc.eval("third == 1/3").unwrap(), Value::Bool(false));
+    assert!(matches!(
+        calc.eval("0.5 + sin(third)").unwrap(),
+        Value::Decimal(_)
+    ));
+    calc.eval("f = 2^0.5").unwrap();
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Float);
+    assert!(matches!(calc.eval("f * 2").unwrap(), Value::Number(_)));
+    assert_eq!(
+        calc.eval("PI").unwrap(),
+        Value::Number(std::f64::consts::PI)
+    );
+}
+
+#[test]
+fn complex_arithmetic_and_functions() {
+    let mut calc = calculator(NumericMode::Complex);
+
+    assert_eq!(display(&mut calc, "sqrt(-4)"), "2i");
+    assert_eq!(display(&mut calc, "nrt(2, -4) + 1"), "1+2i");
+    assert_eq!(display(&mut calc, "ln(-1) / PI"), "1i");
+    assert_eq!(display(&mut calc, "(1 + 2i) * (3 - 4i)"), "11+2i");
+    assert_eq!(display(&mut calc, "(1 + 2i) / (3 - 4i)"), "-0.2+0.4i");
+    assert_eq!(display(&mut calc, "conj(3 + 4i)"), "3-4i");
+    assert_eq!(display(&mut calc, "rect(1, -1)"), "1-1i");
+
+    // Real results are plain numbers
+    assert_eq!(calc.eval("i^2").unwrap(), Value::Number(-1.0));
+    assert_eq!(calc.eval("abs(3 + 4i)").unwrap(), 5.0);
+    assert_eq!(calc.eval("re(3 + 4i) + im(3 + 4i)").unwrap(), 7.0);
+    assert_eq!(calc.eval("arg(-1)").unwrap(), std::f64::consts::PI);
+    assert_eq!(calc.eval("2i == rect(0, 2)").unwrap(), Value::Bool(true));
+    let z = calc.eval("polar(2, PI / 6)").unwrap();
+    assert!(
+        matches!(z, Value::Complex(z) if (z.re - 3f64.sqrt()).abs() < 1e-12 && (z.im - 1.0).abs() < 1e-12)
+    );
+
+    // Impedance of a series RLC circuit at 50 Hz
+    calc.run("R = 50; L = 0.1; C = 1e-5; w = 2 PI * 50; Z = R + i w L + 1 / (i w C)")
+        .unwrap();
+    let magnitude = calc.eval("abs(Z)").unwrap().as_number().unwrap();
+    assert!((magnitude - 291.218378682470).abs() < 1e-9);
+
+    assert_eq!(calc.eval("1 / (0 i)").unwrap_err().code(), "E301");
+    assert_eq!(calc.eval("ln(0)").unwrap_err().code(), "E303");
+    assert_eq!(
+        calc.eval("i < 1").unwrap_err().message(),
+        "Cannot compare complex number with number using \"<\""
+    );
+    assert_eq!(
+        calc.eval("floor(i)").unwrap_err().message(),
+        "\"floor\" expects a number as argument 1, found complex number"
+    );
+}
+
+#[test]
+fn complex_mode_is_opt_in() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(calc.eval("i").unwrap_err().code(), "E304");
+    assert_eq!(calc.eval("sqrt(-4)").unwrap_err().code(), "E307");
+    assert!(calc
+        .eval("(-8)^(1/3)")
+        .unwrap()
+        .as_number()
+        .unwrap()
+        .is_nan());
+
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Complex);
+    calc.eval("z = (-8)^(1/3)").unwrap();
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Float);
+    assert_eq!(calc.eval("i").unwrap_err().code(), "E304");
+
+    // Complex values outlive the mode
+    assert!(matches!(calc.eval("z * 2").unwrap(), Value::Complex(_)));
+    assert!((calc.eval("abs(z)").unwrap().as_number().unwrap() - 2.0).abs() < 1e-12);
+}
+
+#[test]
+fn integer_arithmetic_is_exact() {
+    let mut calc = calculator(NumericMode::Integer);
+
+    assert_eq!(
+        display(&mut calc, "2^200"),
+        "1606938044258990275541962092341162602522202993782792835301376"
+    );
+    assert_eq!(
+        display(&mut calc, "factorial(50)"),
+        "30414093201713378043612608166064768844377641568960512000000000000"
+    );
+    assert_eq!(
+        display(&mut calc, "binomial(100, 50)"),
+        "100891344545564193334812497256"
+    );
+    assert!(matches!(calc.eval("6 / 3 - 1").unwrap(), Value::Integer(_)));
+    assert!(matches!(calc.eval("abs(-5)").unwrap(), Value::Integer(_)));
+    assert!(calc.eval("10^30 + 1 > 10^30").unwrap() == Value::Bool(true));
+    assert_eq!(display(&mut calc, "binomial(5, 7) + binomial(5, -1)"), "0");
+
+    // Results that are not integers are floats
+    assert_eq!(calc.eval("7 / 2").unwrap(), Value::Number(3.5));
+    assert_eq!(calc.eval("2^-1").unwrap(), Value::Number(0.5));
+    assert_eq!(calc.eval("1.5 + 1").unwrap(), Value::Number(2.5));
+    assert_eq!(calc.eval("sqrt(2)").unwrap(), 2f64.sqrt());
+    assert_eq!(display(&mut calc, "sqrt(16)"), "4");
+
+    assert_eq!(calc.eval("factorial(-1)").unwrap_err().code(), "E307");
+    assert_eq!(calc.eval("factorial(5/2)").unwrap_err().code(), "E307");
+    assert_eq!(calc.eval("0^-1").unwrap_err().code(), "E301");
+}
+
+#[test]
+fn integer_division_rounds_down() {
+    let mut integers = calculator(NumericMode::Integer);
+    let mut rationals = calculator(NumericMode::Rational);
+    let mut decimals = calculator(NumericMode::decimal(20));
+    let mut floats = Calculator::new();
+
+    // The quotient is rounded down and the remainder has the sign of the divisor
+    for calc in [&mut integers, &mut rationals, &mut decimals, &mut floats] {
+        assert_eq!(display(calc, "7 // 2"), "3");
+        assert_eq!(display(calc, "-7 // 2"), "-4");
+        assert_eq!(display(calc, "7 % -2"), "-1");
+        assert_eq!(display(calc, "-7 % 2"), "1");
+        assert_eq!(display(calc, "7.5 // 2 + -7.5 % 2"), "3.5");
+        assert_eq!(calc.eval("1 // 0").unwrap_err().code(), "E301");
+        assert_eq!(calc.eval("1 % 0").unwrap_err().code(), "E301");
+    }
+    assert_eq!(display(&mut rationals, "(-7/2) % (1/3)"), "1/6");
+    assert_eq!(display(&mut floats, "factorial(5)"), "120");
+    assert_eq!(floats.eval("factorial(171)").unwrap(), f64::INFINITY);
+
+    let mut complexes = calculator(NumericMode::Complex);
+    assert_eq!(
+        complexes.eval("i // 2").unwrap_err().message(),
+        "Cannot divide complex number by number"
+    );
+}
@@ -0,0 +1,223 @@
+use ise_rust_calc_lib::expression::{
+    ArithmeticNode, ArithmeticNodeKind, ParseOptions, ParserError,
+};
+use ise_rust_calc_lib::lexer::{Lexer, Span};
+use ise_rust_calc_lib::statement::{self, Statement};
+
+fn rpn(input: &str) -> String {
+    let toks = Lexer::new(input.to_string()).all().unwrap();
+    ArithmeticNode::parse(&toks).unwrap().into_rpn()
+}
+
+fn error(input: &str) -> ParserError {
+    let toks = Lexer::new(input.to_string()).all().unwrap();
+    ArithmeticNode::parse(&toks).unwrap_err()
+}
+
+#[test]
+fn precedence() {
+    assert_eq!(rpn("1 + 2 * 3"), "(1 (2 3 *) +)");
+    assert_eq!(rpn("(1 + 2) * 3"), "((1 2 +) 3 *)");
+    assert_eq!(rpn("-2 + sin(x)"), "((2 -) (x sin) +)");
+}
+
+#[test]
+fn associativity() {
+    assert_eq!(rpn("1 - 2 - 3"), "((1 2 -) 3 -)");
+    assert_eq!(rpn("8 / 4 / 2"), "((8 4 /) 2 /)");
+    assert_eq!(rpn("7 // 2 % 3 * 4"), "(((7 2 //) 3 %) 4 *)");
+    assert_eq!(rpn("2 ^ 3 ^ 2"), "(2 (3 2 ^) ^)");
+}
+
+#[test]
+fn unary_minus() {
+    assert_eq!(rpn("-2 ^ 2"), "((2 2 ^) -)");
+    assert_eq!(rpn("2 ^ -1"), "(2 (1 -) ^)");
+    assert_eq!(rpn("-2 * 3"), "((2 -) 3 *)");
+    assert_eq!(rpn("- -2"), "((2 -) -)");
+}
+
+#[test]
+fn deep_nesting() {
+    let input = format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000));
+    assert!(matches!(error(&input), ParserError::NestingTooDeep(_)));
+
+    let input = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+    assert_eq!(rpn(&input), "1");
+
+    // Each operator of a chain nests the operation before it
+    let input = vec!["1"; 100_000].join("+");
+    assert!(matches!(error(&input), ParserError::NestingTooDeep(_)));
+    let input = vec!["1"; 100_000].join("*");
+    assert!(matches!(error(&input), ParserError::NestingTooDeep(_)));
+    let input = vec!["1"; 1000].join(" + ");
+    let toks = Lexer::new(input).all().unwrap();
+    assert!(ArithmeticNode::parse(&toks).is_ok());
+}
+
+#[test]
+fn empty_expressions() {
+    assert!(matches!(error(""), ParserError::EmptyExpression(_)));
+    assert!(matches!(error("()"), ParserError::EmptyExpression(_)));
+    assert!(matches!(error("1 +"), ParserError::EmptyExpression(_)));
+    assert!(matches!(error("log(, 2)"), ParserError::EmptyExpression(_)));
+
+    let toks = Lexer::new("x =".to_string()).all().unwrap();
+    assert!(matches!(
+        Statement::parse(&toks),
+        Err(statement::ParserError::Arithmetic(
+            ParserError::EmptyExpression(_)
+        ))
+    ));
+}
+
+#[test]
+fn unbalanced_parens() {
+    assert!(matches!(error("(1 + 2"), ParserError::UnclosedParen(_)));
+    assert!(matches!(error("sin(1"), ParserError::UnclosedParen(_)));
+    assert!(matches!(error("1 + 2)"), ParserError::UnexpectedToken(_)));
+}
+
+#[test]
+fn functions() {
+    // Functions are resolved during evaluation, so any name parses
+    assert_eq!(rpn("foo(1)"), "(1 foo)");
+    assert_eq!(rpn("nrt(2, x + 1)"), "(2 (x 1 +) nrt)");
+    assert_eq!(rpn("f()"), "(f)");
+    assert!(matches!(error("f(1 2)"), ParserError::UnexpectedToken(_)));
+}
+
+#[test]
+fn unexpected_tokens() {
+    assert!(matches!(error("2 3"), ParserError::UnexpectedToken(_)));
+    assert!(matches!(error("x 2"), ParserError::UnexpectedToken(_)));
+    assert!(matches!(
+        error("* 2"),
+        ParserError::InvalidUnaryOperator(..)
+    ));
+
+    // Tokens are written as in the source
+    assert_eq!(error("2 3.5e2").message(), "Unexpected token: \"3.5e2\"");
+    assert_eq!(error("1 0xFF").message(), "Unexpected token: \"0xFF\"");
+    assert_eq!(error("(1 + 2))").message(), "Unexpected token: \")\"");
+    assert_eq!(error("1 + 2 unit").message(), "Unexpected token: \"unit\"");
+}
+
+#[test]
+fn implicit_multiplication() {
+    assert_eq!(rpn("2x"), "(2 x *)");
+    assert_eq!(rpn("3(a + b)"), "(3 (a b +) *)");
+    assert_eq!(rpn("(a + b)(a - b)"), "((a b +) (a b -) *)");
+    assert_eq!(rpn("2PI"), "(2 PI *)");
+    assert_eq!(rpn("2 sin(x)"), "(2 (x sin) *)");
+}
+
+#[test]
+fn implicit_multiplication_precedence() {
+    assert_eq!(rpn("2x^2"), "(2 (x 2 ^) *)");
+    assert_eq!(rpn("1/2x"), "(1 (2 x *) /)");
+    assert_eq!(rpn("-2x"), "((2 -) x *)");
+    assert_eq!(rpn("a b + c"), "((a b *) c +)");
+    assert_eq!(rpn("2x y"), "((2 x *) y *)");
+}
+
+#[test]
+fn implicit_multiplication_spans() {
+    let source = "1 + 2(x - 1)";
+    let toks = Lexer::new(source.to_string()).all().unwrap();
+    let node = ArithmeticNode::parse(&toks).unwrap();
+    let ArithmeticNodeKind::Add(_, mul) = node.kind else {
+        panic!("expected an addition");
+    };
+    assert_eq!(
+        mul.span.underline(source, None),
+        "1 + 2(x - 1)\n    --------"
+    );
+}
+
+#[test]
+fn strict_parsing() {
+    let strict = ParseOptions::strict();
+    for input in ["2x", "(1)(2)", "3(a + b)"] {
+        let toks = Lexer::new(input.to_string()).all().unwrap();
+        assert!(matches!(
+            ArithmeticNode::parse_with(&toks, &Span::default(), &strict),
+            Err(ParserError::UnexpectedToken(_))
+        ));
+    }
+}
+
+#[test]
+fn comparisons_and_logic() {
+    assert_eq!(rpn("a + 1 < b * 2"), "((a 1 +) (b 2 *) <)");
+    assert_eq!(
+        rpn("a < b and b <= c or d"),
+        "(((a b <) (b c <=) and) d or)"
+    );
+    assert_eq!(rpn("a or b and c"), "(a (b c and) or)");
+    assert_eq!(rpn("not a == b and c"), "(((a b ==) not) c and)");
+    assert_eq!(rpn("-a != 2x"), "((a -) (2 x *) !=)");
+    assert_eq!(rpn("true or not false"), "(true (false not) or)");
+}
+
+#[test]
+fn unit_conversions() {
+    assert_eq!(rpn("72 km/h to m/s"), "(((72 km *) h /) (m s /) to)");
+    assert_eq!(rpn("x to m == y"), "((x m to) y ==)");
+    assert_eq!(rpn("a + b to c to d"), "(((a b +) c to) d to)");
+}
+
+#[test]
+fn intervals() {
+    assert_eq!(rpn("[1.9, 2.1]"), "(1.9 2.1 [])");
+    assert_eq!(rpn("[-a, 2 * b] ^ 2"), "(((a -) (2 b *) []) 2 ^)");
+    assert_eq!(rpn("[1, 2] mm"), "((1 2 []) mm *)");
+    assert_eq!(rpn("sin([0, PI])"), "((0 PI []) sin)");
+
+    assert!(matches!(error("[1, 2"), ParserError::UnclosedBracket(_)));
+    assert!(matches!(error("[1"), ParserError::UnclosedBracket(_)));
+    assert!(matches!(error("[1 2]"), ParserError::UnexpectedToken(_)));
+    assert!(matches!(error("[1, ]"), ParserError::EmptyExpression(_)));
+    assert!(matches!(error("[1, 2)"), ParserError::UnexpectedToken(_)));
+}
+
+#[test]
+fn angles() {
+    assert_eq!(rpn("sin(30°)"), "(30° sin)");
+    assert_eq!(rpn("-1.5rad * 2"), "((1.5rad -) 2 *)");
+    assert_eq!(rpn("2^100grad"), "(2 100grad ^)");
+    assert_eq!(rpn("30° x"), "(30° x *)");
+}
+
+#[test]
+fn uncertainties() {
+    assert_eq!(rpn("9.81 +- 0.02"), "(9.81 0.02 +-)");
+    assert_eq!(rpn("a + b +- s"), "(a (b s +-) +)");
+    assert_eq!(rpn("a +- 2 s * 3"), "(a ((2 s *) 3 *) +-)");
+    assert_eq!(rpn("(1 ± 0.1) m"), "((1 0.1 +-) m *)");
+    assert_eq!(rpn("a +- s < b"), "((a s +-) b <)");
+}
+
+#[test]
+fn conditionals() {
+    assert_eq!(rpn("if(a > 0, a, -a)"), "((a 0 >) a (a -) ?:)");
+    assert_eq!(rpn("a or b ? 1 + 2 : 3"), "((a b or) (1 2 +) 3 ?:)");
+    assert_eq!(rpn("a ? b : c ? d : e"), "(a b (c d e ?:) ?:)");
+    assert_eq!(rpn("a ? b ? c : d : e"), "(a (b c d ?:) e ?:)");
+    assert_eq!(rpn("(a ? b : c) * 2"), "((a b c ?:) 2 *)");
+
+    assert!(matches!(
+        error("if(a, b)"),
+        ParserError::ConditionalArguments(_)
+    ));
+    assert!(matches!(
+        error("if + 1"),
+        ParserError::ConditionalArguments(_)
+    ));
+    assert!(matches!(error("a ? b"), ParserError::MissingColon(_)));
+    assert!(matches!(
+        error("a ? b , c"),
+        ParserError::UnexpectedToken(_)
+    ));
+    assert!(matches!(error("a ? b :"), ParserError::EmptyExpression(_)));
+}
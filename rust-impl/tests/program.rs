@@ -0,0 +1,214 @@
+use std::cell::Cell;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use ise_rust_calc_lib::evaluator::{ArithmeticError, Evaluator, Observer, StatementOutcome};
+use ise_rust_calc_lib::format::{Format, Notation};
+use ise_rust_calc_lib::lexer::Lexer;
+use ise_rust_calc_lib::program::Program;
+use ise_rust_calc_lib::statement::Statement;
+use ise_rust_calc_lib::{CalcError, Calculator};
+
+fn parse(source: &str) -> Program {
+    let toks = Lexer::new(source.to_string()).all().unwrap();
+    Program::parse(&toks).unwrap()
+}
+
+#[test]
+fn separators_and_comments() {
+    let program = parse("# constants\na = 1; b = 2\n\n  # sum\na + b # trailing comment\n;;");
+    assert_eq!(program.into_str(), "a <- 1\nb <- 2\n(a b +)");
+}
+
+#[test]
+fn newlines_within_parenthesis() {
+    let program = parse("x = (1 +\n  2)\nx * 2");
+    assert_eq!(program.statements.len(), 2);
+    assert!(Program::parse(&Lexer::new("(1; 2)".to_string()).all().unwrap()).is_err());
+}
+
+#[test]
+fn function_definitions() {
+    let program = parse("f(x, y) = x y + 1\nnone() = 2\nf(1, 2)");
+    assert_eq!(
+        program.into_str(),
+        "f(x, y) <- ((x y *) 1 +)\nnone() <- 2\n(1 2 f)"
+    );
+    assert_eq!(program.statements[0].span().end.offset, 17);
+}
+
+#[test]
+fn runs_statements_in_order() {
+    let mut eval = Evaluator::new();
+    let values = eval
+        .eval_program(&parse("a = 2\nb = a * 3\na + b"))
+        .unwrap();
+    assert_eq!(values, vec![2.0, 6.0, 8.0]);
+}
+
+#[test]
+fn statement_outcomes() {
+    let mut eval = Evaluator::new();
+    let program = parse("x = 2\nsq(t) = t^2\nunit pace = 0.75 m\nsq(x) + 1");
+    let outcomes = program
+        .statements
+        .iter()
+        .map(|stmnt| eval.eval_statement(stmnt).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        outcomes[0],
+        StatementOutcome::Assigned {
+            name: "x".into(),
+            value: 2.0.into()
+        }
+    );
+    assert_eq!(
+        outcomes[1],
+        StatementOutcome::FunctionDefined {
+            name: "sq".into(),
+            params: vec!["t".into()]
+        }
+    );
+    assert_eq!(
+        outcomes[2],
+        StatementOutcome::UnitDefined {
+            name: "pace".into()
+        }
+    );
+    assert_eq!(outcomes[3], StatementOutcome::Value(5.0.into()));
+    assert_eq!(outcomes[1].value(), None);
+    assert_eq!(outcomes[3].clone().into_value(), Some(5.0.into()));
+
+    assert_eq!(outcomes[0].to_string(), "Setting 'x' = 2");
+    assert_eq!(outcomes[1].to_string(), "Defining 'sq(t)'");
+    assert_eq!(
+        outcomes[3].describe(&Format::new(Notation::Fixed(1))),
+        "Expression resolves to 5.0"
+    );
+}
+
+#[test]
+fn observer_traces_statements() {
+    let trace = Arc::new(Mutex::new(Vec::new()));
+    let mut eval = Evaluator::new();
+    let sink = Arc::clone(&trace);
+    eval.set_observer(move |_: &_, outcome: &StatementOutcome| {
+        sink.lock().unwrap().push(outcome.to_string())
+    });
+
+    eval.eval_program(&parse("a = 2\nf(x) = x + a\nf(1)"))
+        .unwrap();
+    assert!(eval.eval_program(&parse("a / 0")).is_err());
+    assert_eq!(
+        *trace.lock().unwrap(),
+        vec![
+            "Setting 'a' = 2",
+            "Defining 'f(x)'",
+            "Expression resolves to 3"
+        ]
+    );
+
+    // Without an observer statements run untraced
+    assert!(eval.take_observer().is_some());
+    eval.eval_program(&parse("a + 1")).unwrap();
+    assert_eq!(trace.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn observers_run_on_other_threads() {
+    let (sender, receiver) = mpsc::channel();
+    let mut eval = Evaluator::new();
+    // A Cell is not Sync, observers only need to be Send
+    let count = Cell::new(0);
+    eval.set_observer(move |_: &_, outcome: &StatementOutcome| {
+        count.set(count.get() + 1);
+        sender.send(format!("{}. {outcome}", count.get())).unwrap()
+    });
+    thread::spawn(move || eval.eval_program(&parse("a = 1\na + 1")).unwrap())
+        .join()
+        .unwrap();
+
+    assert_eq!(
+        receiver.try_iter().collect::<Vec<_>>(),
+        vec!["1. Setting 'a' = 1", "2. Expression resolves to 2"]
+    );
+}
+
+/// Counts the statements that succeed and keeps the codes of the errors
+#[derive(Default)]
+struct Tally {
+    succeeded: Arc<Mutex<usize>>,
+    errors: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Observer for Tally {
+    fn statement(&mut self, _: &Statement, _: &StatementOutcome) {
+        *self.succeeded.lock().unwrap() += 1;
+    }
+
+    fn error(&mut self, _: &Statement, error: &ArithmeticError) {
+        self.errors.lock().unwrap().push(error.code());
+    }
+}
+
+#[test]
+fn observer_sees_errors() {
+    let tally = Tally::default();
+    let (succeeded, errors) = (Arc::clone(&tally.succeeded), Arc::clone(&tally.errors));
+    let mut eval = Evaluator::new();
+    eval.set_observer(tally);
+
+    assert!(eval
+        .eval_program(&parse("a = 1\nb = a / 0\nc = 2"))
+        .is_err());
+    assert_eq!(*succeeded.lock().unwrap(), 1);
+    assert_eq!(*errors.lock().unwrap(), vec!["E301"]);
+}
+
+#[test]
+fn reports_failing_statement() {
+    let source = "a = 2\nb = a / 0\nc = 1";
+    let mut eval = Evaluator::new();
+    let err = eval.eval_program(&parse(source)).unwrap_err();
+
+    assert_eq!(err.index, 1);
+    assert_eq!(err.statement.begin.line, 1);
+    assert_eq!(
+        err.contextualize(source),
+        "2 | b = a / 0\n  |     ----- << In statement 2: Division by Zero"
+    );
+    // The statements after the failing one are not run
+    assert_eq!(eval.get_variable("c"), None);
+}
+
+#[test]
+fn calculator_reports_failing_statement() {
+    let mut calc = Calculator::new();
+    let source = "a = 2\nb = a / 0\nc = 1";
+
+    let err = calc.run(source).unwrap_err();
+    let CalcError::Program(ref program_error) = err else {
+        panic!("{err:?} does not locate the statement");
+    };
+    assert_eq!(program_error.index, 1);
+    assert_eq!(err.code(), "E301");
+    assert_eq!(err.message(), "In statement 2: Division by Zero");
+    assert_eq!(
+        err.contextualize(source),
+        "2 | b = a / 0\n  |     ----- << [E301] In statement 2: Division by Zero"
+    );
+
+    // A lone statement is the whole input
+    let err = calc.eval("a / 0").unwrap_err();
+    assert!(matches!(err, CalcError::Arithmetic(_)));
+    assert_eq!(err.message(), "Division by Zero");
+}
+
+#[test]
+fn calculator_returns_last_value() {
+    let mut calc = Calculator::new();
+    assert_eq!(calc.eval("a = 4; a * a").unwrap(), 16.0);
+    assert_eq!(calc.run("1; 2\n3").unwrap(), vec![1.0, 2.0, 3.0]);
+    assert_eq!(calc.eval("# only a comment").unwrap_err().code(), "E205");
+}
@@ -0,0 +1,189 @@
+use ise_rust_calc_lib::evaluator::NumericMode;
+use ise_rust_calc_lib::units::{Dimension, NamedUnit};
+use ise_rust_calc_lib::{Calculator, Value};
+use num_rational::BigRational;
+
+mod common;
+use common::display;
+
+#[test]
+fn units_combine_and_convert() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(display(&mut calc, "5 m / 2 s"), "2.5 m/s");
+    assert_eq!(display(&mut calc, "3 ft + 2 m"), "9.561679790026247 ft");
+    assert_eq!(display(&mut calc, "72 km/h to m/s"), "20 m/s");
+    assert_eq!(display(&mut calc, "1 mi to km"), "1.609344 km");
+    assert_eq!(display(&mut calc, "(3 m)^2"), "9 m^2");
+    assert_eq!(display(&mut calc, "(4 m^2)^0.5"), "2 m");
+    assert_eq!(display(&mut calc, "9.81 m/s^2 * 70 kg"), "686.7 m*kg/s^2");
+    assert_eq!(display(&mut calc, "5 N * 2 m to J"), "10 J");
+    assert_eq!(display(&mut calc, "1 / 4 s"), "0.25 s^-1");
+    assert_eq!(display(&mut calc, "7 m % 2 m"), "1 m");
+
+    // Quantities of no dimension are plain numbers
+    assert_eq!(calc.eval("1 km / 1 m").unwrap(), 1000.0);
+    assert_eq!(calc.eval("7 m // 2 m").unwrap(), 3.0);
+    assert_eq!(calc.eval("1 µm == 1000 nm").unwrap(), Value::Bool(true));
+    assert_eq!(calc.eval("1 ft < 1 m").unwrap(), Value::Bool(true));
+
+    // Variables take precedence over units
+    assert_eq!(display(&mut calc, "m = 3; m s"), "3 s");
+}
+
+#[test]
+fn names_are_units_only_after_a_value_or_to() {
+    let mut calc = Calculator::new();
+
+    for input in ["bar", "bar * 4 - 31", "h", "1 / s", "sqrt(h)", "m to km"] {
+        let err = calc.eval(input).unwrap_err();
+        assert_eq!(err.code(), "E304", "{input}");
+    }
+    assert_eq!(
+        calc.eval("bar * 4 - 31").unwrap_err().message(),
+        "Undefined variable: \"bar\""
+    );
+
+    assert_eq!(display(&mut calc, "2 bar"), "2 bar");
+    assert_eq!(display(&mut calc, "1 kg*m/s^2 to N"), "1 N");
+    assert_eq!(display(&mut calc, "(2 m)^2 / s"), "4 m^2/s");
+    assert_eq!(display(&mut calc, "3600 s to h"), "1 h");
+}
+
+#[test]
+fn affine_temperatures() {
+    let mut calc = Calculator::new();
+
+    assert_eq!(display(&mut calc, "100 °C to °F"), "212 °F");
+    assert_eq!(display(&mut calc, "-40 degF to degC"), "-40 degC");
+    assert_eq!(display(&mut calc, "0 K to °F"), "-459.67 °F");
+    assert_eq!(calc.eval("0 °C == 273.15 K").unwrap(), Value::Bool(true));
+    assert_eq!(calc.eval("20 °C > 60 °F").unwrap(), Value::Bool(true));
+
+    // Added to a temperature, a temperature is a difference
+    assert_eq!(display(&mut calc, "20 °C + 5 K"), "25 °C");
+    assert_eq!(display(&mut calc, "50 °F - 9 K"), "33.8 °F");
+
+    // Two absolute temperatures are kelvins apart, and have no sum
+    assert_eq!(display(&mut calc, "0 °C - 32 °F"), "0 K");
+    assert_eq!(display(&mut calc, "(0 °C - 32 °F) to K"), "0 K");
+    assert_eq!(display(&mut calc, "30 °C - 20 degC"), "10 K");
+    assert_eq!(display(&mut calc, "20 °C - 50 °F"), "10 K");
+    let err = calc.eval("20 °C + 20 °C").unwrap_err();
+    assert_eq!(err.code(), "E310");
+    assert_eq!(
+        err.message(),
+        "Cannot add °C (temperature) to °C (temperature), both are absolute temperatures"
+    );
+}
+
+#[test]
+fn incompatible_units() {
+    let mut calc = Calculator::new();
+
+    let err = calc.eval("1 kg + 1 s").unwrap_err();
+    assert_eq!(err.code(), "E310");
+    assert_eq!(
+        err.contextualize("1 kg + 1 s"),
+        "1 kg + 1 s\n     - << [E310] Cannot add s (time) to kg (mass)"
+    );
+    assert_eq!(
+        calc.eval("2 m to s").unwrap_err().message(),
+        "Cannot convert m (length) to s (time)"
+    );
+    assert_eq!(
+        calc.eval("1 m < 1").unwrap_err().message(),
+        "Cannot compare m (length) with number using \"<\""
+    );
+    assert_eq!(
+        calc.eval("(1 m)^0.5").unwrap_err().message(),
+        "Cannot raise m (length) to the power of 0.5, its unit would have a fractional exponent"
+    );
+    // Unit exponents are bounded like exact powers
+    assert_eq!(
+        calc.eval("(1 km)^100000000 / (1 m)^100000000")
+            .unwrap_err()
+            .message(),
+        "Cannot raise km (length) to the power of 100000000, its unit would have too large an exponent"
+    );
+    assert_eq!(
+        calc.eval("(1 km)^100000 * (1 km)^100000")
+            .unwrap_err()
+            .code(),
+        "E310"
+    );
+    assert_eq!(calc.eval("sin(2 m)").unwrap_err().code(), "E309");
+    assert_eq!(calc.eval("metre").unwrap_err().code(), "E304");
+}
+
+#[test]
+fn user_defined_units() {
+    let mut calc = Calculator::new();
+
+    calc.run("unit furlong = 201.168 m").unwrap();
+    assert_eq!(display(&mut calc, "10 furlong to km"), "2.01168 km");
+    calc.run("unit dozen = 12").unwrap();
+    assert_eq!(calc.eval("3 dozen").unwrap(), 36.0);
+    assert_eq!(display(&mut calc, "30 to dozen"), "2.5 dozen");
+
+    // Names starting with ° are left to units
+    calc.run("unit °Ra = (5/9) K").unwrap();
+    assert_eq!(display(&mut calc, "9 °Ra to K"), "5 K");
+    for input in ["° = 3", "°x(y) = y", "f(°y) = 1"] {
+        let err = calc.run(input).unwrap_err();
+        assert_eq!(err.code(), "E212", "{input}");
+    }
+    assert_eq!(
+        calc.run("°x = 1").unwrap_err().message(),
+        "Invalid name: \"°x\", only units are named with '°'"
+    );
+
+    let err = calc.run("unit nothing = 0 m").unwrap_err();
+    assert_eq!(err.code(), "E310");
+    assert_eq!(
+        err.message(),
+        "Invalid unit \"nothing\": a unit must be positive"
+    );
+
+    // Hosts can add units directly
+    let fathom = NamedUnit::new(
+        "fathom",
+        BigRational::new(18288.into(), 10000.into()),
+        Dimension::LENGTH,
+    );
+    calc.evaluator_mut().units_mut().define(fathom).unwrap();
+    assert_eq!(display(&mut calc, "2 fathom to ft"), "12 ft");
+    assert!(calc.evaluator().units().contains("km"));
+    assert!(!calc.evaluator().units().contains("kft"));
+}
+
+#[test]
+fn units_follow_the_numeric_mode() {
+    let mut calc = Calculator::new();
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Rational);
+
+    assert_eq!(display(&mut calc, "100 °F to °C"), "(340/9) °C");
+    assert!(calc.eval("1 mi to ft").unwrap().is_exact());
+
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Integer);
+    assert_eq!(display(&mut calc, "3 ft to in"), "36 in");
+    assert_eq!(
+        display(&mut calc, "2^70 kg to g"),
+        "1180591620717411303424000 g"
+    );
+}
+
+#[test]
+fn units_in_interval_mode() {
+    let mut calc = Calculator::new();
+    calc.evaluator_mut().set_numeric_mode(NumericMode::Interval);
+
+    assert_eq!(display(&mut calc, "2 m^2"), "[2, 2] m^2");
+    assert_eq!(display(&mut calc, "(3 m)^2 to cm^2"), "[90000, 90000] cm^2");
+    assert_eq!(
+        display(&mut calc, "(4 m^2)^0.5"),
+        "[1.9999999999999996, 2.0000000000000004] m"
+    );
+    assert_eq!(display(&mut calc, "[1, 2] m^-1"), "[1, 2] m^-1");
+    assert_eq!(calc.eval("(2 m)^0.5").unwrap_err().code(), "E310");
+}